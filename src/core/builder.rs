@@ -0,0 +1,439 @@
+/*
+ * Copyright 2025 SimplePixelFont
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::core::tables::pixmap::{fits, pack_pixels, pixmap_byte_length};
+use crate::core::*;
+use crate::{format, vec, String, Vec};
+
+/// Returns `length` as the index of the next table or record, which links store as a `u8`.
+fn next_index(length: usize, what: &str) -> u8 {
+    u8::try_from(length).unwrap_or_else(|_| panic!("a layout can only reference 256 {}", what))
+}
+
+/// Returns the position of `index` within `links`, appending it if it is not linked yet.
+fn link(links: &mut Option<Vec<u8>>, index: u8) -> u8 {
+    let links = links.get_or_insert_with(Vec::new);
+    match links.iter().position(|link| *link == index) {
+        Some(position) => position as u8,
+        None => {
+            links.push(index);
+            next_index(links.len() - 1, "linked tables per table")
+        }
+    }
+}
+
+/// Refers to a [`ColorTable`] added with [`LayoutBuilder::add_color_table`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ColorTableHandle(u8);
+
+/// Refers to a [`PixmapTable`] added with [`LayoutBuilder::add_pixmap_table`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PixmapTableHandle(u8);
+
+/// Refers to a single [`Pixmap`] added with [`LayoutBuilder::add_pixmap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PixmapHandle {
+    table: PixmapTableHandle,
+    index: u8,
+}
+
+/// Refers to a [`CharacterTable`] added with [`LayoutBuilder::add_character_table`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CharacterTableHandle(u8);
+
+impl ColorTableHandle {
+    /// Index of the table within [`Layout::color_tables`].
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl PixmapTableHandle {
+    /// Index of the table within [`Layout::pixmap_tables`].
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl PixmapHandle {
+    /// The table the pixmap belongs to.
+    pub fn table(self) -> PixmapTableHandle {
+        self.table
+    }
+
+    /// Index of the pixmap within [`PixmapTable::pixmaps`].
+    pub fn index(self) -> usize {
+        self.index as usize
+    }
+}
+
+impl CharacterTableHandle {
+    /// Index of the table within [`Layout::character_tables`].
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Assembles a [`Layout`] table by table, returning typed handles to refer to what was added
+/// instead of raw indexes. Links between tables, record indexes and every modifier,
+/// configuration and link flag are filled in by [`LayoutBuilder::build`].
+///
+/// # Panics
+///
+/// Adding more than 256 tables of one kind, or more than 256 pixmaps to one table, panics as
+/// they could not be referenced by a `u8` index.
+#[derive(Debug, Default)]
+pub struct LayoutBuilder {
+    layout: Layout,
+    /// Errors found while adding records, reported by [`LayoutBuilder::build`].
+    errors: Vec<Diagnostic>,
+}
+
+impl LayoutBuilder {
+    /// Creates a builder for an empty [`Layout`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the [`Layout::version`] to build.
+    pub fn version(&mut self, version: Version) -> &mut Self {
+        self.layout.version = version;
+        self
+    }
+
+    /// Sets whether the built layout is [`Layout::compact`].
+    pub fn compact(&mut self, compact: bool) -> &mut Self {
+        self.layout.compact = compact;
+        self
+    }
+
+    /// Adds an empty color table, to be filled with [`ColorTableBuilder::color`].
+    pub fn add_color_table(&mut self) -> ColorTableBuilder<'_> {
+        let handle = ColorTableHandle(next_index(self.layout.color_tables.len(), "color tables"));
+        self.layout.color_tables.push(ColorTable::default());
+        ColorTableBuilder {
+            table: &mut self.layout.color_tables[handle.index()],
+            handle,
+        }
+    }
+
+    /// Adds an empty pixmap table whose pixel values index into `color_tables`, in order.
+    pub fn add_pixmap_table(
+        &mut self,
+        color_tables: &[ColorTableHandle],
+    ) -> PixmapTableBuilder<'_> {
+        let handle =
+            PixmapTableHandle(next_index(self.layout.pixmap_tables.len(), "pixmap tables"));
+        let mut table = PixmapTable::default();
+        for color_table in color_tables {
+            link(&mut table.color_table_indexes, color_table.0);
+        }
+        self.layout.pixmap_tables.push(table);
+        PixmapTableBuilder {
+            table: &mut self.layout.pixmap_tables[handle.index()],
+            handle,
+        }
+    }
+
+    /// Adds a `width` by `height` pixmap to `table`. `pixels` holds one value per pixel, row
+    /// by row, and is packed at `bits_per_pixel` bits each.
+    ///
+    /// If `pixels` does not hold exactly `width * height` values, or a value does not fit in
+    /// `bits_per_pixel` bits, the pixmap is left blank and [`LayoutBuilder::build`] fails
+    /// with an error diagnostic for it.
+    pub fn add_pixmap(
+        &mut self,
+        table: PixmapTableHandle,
+        width: u8,
+        height: u8,
+        bits_per_pixel: u8,
+        pixels: &[u8],
+    ) -> PixmapHandle {
+        let pixmaps = &mut self.layout.pixmap_tables[table.index()].pixmaps;
+        let index = next_index(pixmaps.len(), "pixmaps per table");
+
+        let pixel_count = width as usize * height as usize;
+        let error = if pixels.len() != pixel_count {
+            Some(format!(
+                "{} pixel values were given but a {}x{} pixmap has {} pixels",
+                pixels.len(),
+                width,
+                height,
+                pixel_count
+            ))
+        } else {
+            pixels
+                .iter()
+                .enumerate()
+                .find(|(_, value)| !fits(**value, bits_per_pixel))
+                .map(|(pixel, value)| {
+                    format!(
+                        "pixel {} has value {}, which does not fit in {} bits per pixel",
+                        pixel, value, bits_per_pixel
+                    )
+                })
+        };
+
+        let data = match error {
+            Some(message) => {
+                self.errors.push(Diagnostic {
+                    severity: Severity::Error,
+                    table_type: Some(TableType::Pixmap),
+                    table_index: Some(table.index()),
+                    record_index: Some(index as usize),
+                    message,
+                });
+                vec![0; pixmap_byte_length(width, height, bits_per_pixel)]
+            }
+            None => pack_pixels(pixels, bits_per_pixel),
+        };
+        pixmaps.push(Pixmap {
+            custom_width: Some(width),
+            custom_height: Some(height),
+            custom_bits_per_pixel: Some(bits_per_pixel),
+            data,
+        });
+
+        PixmapHandle { table, index }
+    }
+
+    /// Adds an empty character table, to be filled with [`CharacterTableBuilder::add_character`].
+    pub fn add_character_table(&mut self) -> CharacterTableBuilder<'_> {
+        let handle = CharacterTableHandle(next_index(
+            self.layout.character_tables.len(),
+            "character tables",
+        ));
+        self.layout.character_tables.push(CharacterTable::default());
+        CharacterTableBuilder {
+            table: &mut self.layout.character_tables[handle.index()],
+            handle,
+        }
+    }
+
+    /// Adds a font to the layout's font table, to be linked to its character tables with
+    /// [`FontBuilder::character_table`].
+    pub fn add_font(
+        &mut self,
+        name: impl Into<String>,
+        author: impl Into<String>,
+    ) -> FontBuilder<'_> {
+        if self.layout.font_tables.is_empty() {
+            self.layout.font_tables.push(FontTable::default());
+        }
+        let table = &mut self.layout.font_tables[0];
+
+        table.fonts.push(Font {
+            name: name.into(),
+            author: author.into(),
+            ..Default::default()
+        });
+
+        let font = table.fonts.len() - 1;
+        FontBuilder { table, font }
+    }
+
+    /// Finishes the layout, filling in record indexes and flags, and checks it with
+    /// [`Layout::validate`]. Returns every error diagnostic if the layout is not valid, for
+    /// example when a pixel value does not fit in the pixmap's linked color tables, along
+    /// with those found by [`LayoutBuilder::add_pixmap`].
+    pub fn build(self) -> Result<Layout, Vec<Diagnostic>> {
+        let mut layout = self.layout;
+        let mut errors = self.errors;
+
+        for (table_index, table) in layout.pixmap_tables.iter_mut().enumerate() {
+            for (index, pixmap) in table.pixmaps.iter_mut().enumerate() {
+                for (name, constant, custom) in [
+                    ("width", table.constant_width, &mut pixmap.custom_width),
+                    ("height", table.constant_height, &mut pixmap.custom_height),
+                    (
+                        "bits per pixel",
+                        table.constant_bits_per_pixel,
+                        &mut pixmap.custom_bits_per_pixel,
+                    ),
+                ] {
+                    let Some(constant) = constant else {
+                        continue;
+                    };
+                    if *custom != Some(constant) {
+                        errors.push(Diagnostic {
+                            severity: Severity::Error,
+                            table_type: Some(TableType::Pixmap),
+                            table_index: Some(table_index),
+                            record_index: Some(index),
+                            message: format!(
+                                "{} is {} but the table's constant {} is {}",
+                                name,
+                                custom.unwrap_or_default(),
+                                name,
+                                constant
+                            ),
+                        });
+                    }
+                    *custom = None;
+                }
+            }
+        }
+
+        for table in &mut layout.character_tables {
+            let links = table.pixmap_table_indexes.as_ref().map_or(0, Vec::len);
+            let in_order = table
+                .characters
+                .iter()
+                .enumerate()
+                .all(|(index, character)| character.pixmap_index == Some(index as u8));
+            for character in &mut table.characters {
+                if in_order {
+                    character.pixmap_index = None;
+                }
+                if links <= 1 {
+                    character.pixmap_table_index = None;
+                }
+            }
+        }
+
+        layout.normalize();
+        errors.extend(layout.validate().into_iter().filter(Diagnostic::is_error));
+        if errors.is_empty() {
+            Ok(layout)
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+/// Configures a color table added with [`LayoutBuilder::add_color_table`].
+#[derive(Debug)]
+pub struct ColorTableBuilder<'a> {
+    table: &'a mut ColorTable,
+    handle: ColorTableHandle,
+}
+
+impl ColorTableBuilder<'_> {
+    /// Appends a color to the table.
+    pub fn color(self, red: u8, green: u8, blue: u8, alpha: u8) -> Self {
+        self.table.colors.push(Color {
+            custom_alpha: Some(alpha),
+            red,
+            green,
+            blue,
+            ..Default::default()
+        });
+        self
+    }
+
+    /// The handle of the table being configured.
+    pub fn handle(&self) -> ColorTableHandle {
+        self.handle
+    }
+}
+
+/// Configures a pixmap table added with [`LayoutBuilder::add_pixmap_table`].
+#[derive(Debug)]
+pub struct PixmapTableBuilder<'a> {
+    table: &'a mut PixmapTable,
+    handle: PixmapTableHandle,
+}
+
+impl PixmapTableBuilder<'_> {
+    /// Stores the width once for the whole table. Every pixmap must be this wide.
+    pub fn constant_width(self, width: u8) -> Self {
+        self.table.constant_width = Some(width);
+        self
+    }
+
+    /// Stores the height once for the whole table. Every pixmap must be this high.
+    pub fn constant_height(self, height: u8) -> Self {
+        self.table.constant_height = Some(height);
+        self
+    }
+
+    /// Stores the bits per pixel once for the whole table. Every pixmap must use it.
+    pub fn constant_bits_per_pixel(self, bits_per_pixel: u8) -> Self {
+        self.table.constant_bits_per_pixel = Some(bits_per_pixel);
+        self
+    }
+
+    /// The handle of the table being configured.
+    pub fn handle(&self) -> PixmapTableHandle {
+        self.handle
+    }
+}
+
+/// Fills a character table added with [`LayoutBuilder::add_character_table`].
+#[derive(Debug)]
+pub struct CharacterTableBuilder<'a> {
+    table: &'a mut CharacterTable,
+    handle: CharacterTableHandle,
+}
+
+impl CharacterTableBuilder<'_> {
+    /// Maps `code_points` (a `char`, or a `&str` for a ligature) to `glyph`. The glyph's
+    /// pixmap table is linked automatically.
+    pub fn add_character(self, code_points: impl Into<String>, glyph: PixmapHandle) -> Self {
+        let pixmap_table_index = link(&mut self.table.pixmap_table_indexes, glyph.table.0);
+
+        self.table.characters.push(Character {
+            pixmap_index: Some(glyph.index),
+            pixmap_table_index: Some(pixmap_table_index),
+            code_points: code_points.into(),
+            ..Default::default()
+        });
+        self
+    }
+
+    /// The handle of the table being filled.
+    pub fn handle(&self) -> CharacterTableHandle {
+        self.handle
+    }
+}
+
+/// Configures a font added with [`LayoutBuilder::add_font`].
+#[derive(Debug)]
+pub struct FontBuilder<'a> {
+    table: &'a mut FontTable,
+    font: usize,
+}
+
+impl FontBuilder<'_> {
+    /// Sets the [`Font::version`].
+    pub fn version(self, version: u8) -> Self {
+        self.table.fonts[self.font].version = version;
+        self
+    }
+
+    /// Sets the [`Font::font_type`].
+    pub fn font_type(self, font_type: FontType) -> Self {
+        self.table.fonts[self.font].font_type = font_type;
+        self
+    }
+
+    /// Makes `character_table` part of this font, linking it from the font table.
+    pub fn character_table(self, character_table: CharacterTableHandle) -> Self {
+        let index = link(&mut self.table.character_table_indexes, character_table.0);
+        let font = &mut self.table.fonts[self.font];
+        if !font.linked_character_table_indexes.contains(&index) {
+            font.linked_character_table_indexes.push(index);
+        }
+        self
+    }
+}
+
+impl Layout {
+    /// Creates a [`LayoutBuilder`].
+    pub fn builder() -> LayoutBuilder {
+        LayoutBuilder::new()
+    }
+}
@@ -14,23 +14,165 @@
  * limitations under the License.
  */
 
-#![doc(hidden)]
+//! Bit-level reading and writing of `.spf` byte streams.
+//!
+//! Values are packed least significant bit first: the first bit written lands in bit `0` of the
+//! first byte. Reads and writes do not have to be byte aligned, whole bytes written mid-byte
+//! straddle two bytes of the stream, which is how compact layouts are packed. Use
+//! [`ByteWriter::align_to_byte`] and [`ByteReader::align_to_byte`] to continue from the next byte
+//! boundary.
 
+use super::{DeserializeError, SerializeError};
 use crate::Vec;
 
+/// Number of complete bytes buffered by a [`ByteWriter`] before they are handed to its sink.
+const SINK_CHUNK_SIZE: usize = 1024;
+
+/// A destination for serialized bytes, written in order as the layout is encoded.
+///
+/// Implemented for [`Vec<u8>`], for `&mut [u8]` (a fixed-size buffer that fails with
+/// [`SerializeError::BufferFull`] once exhausted) and, with the `std` feature, for any
+/// [`std::io::Write`] through [`WriteSink`].
+pub trait ByteSink {
+    /// Writes all of `bytes` to the sink.
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), SerializeError>;
+    /// Flushes any bytes the sink buffers itself. Called once all bytes have been written.
+    fn flush(&mut self) -> Result<(), SerializeError> {
+        Ok(())
+    }
+}
+
+impl ByteSink for Vec<u8> {
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), SerializeError> {
+        self.extend_from_slice(bytes);
+        Ok(())
+    }
+}
+
+impl ByteSink for &mut [u8] {
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), SerializeError> {
+        if bytes.len() > self.len() {
+            return Err(SerializeError::BufferFull);
+        }
+        let (written, remaining) = core::mem::take(self).split_at_mut(bytes.len());
+        written.copy_from_slice(bytes);
+        *self = remaining;
+        Ok(())
+    }
+}
+
+/// Adapts a [`std::io::Write`] into a [`ByteSink`].
+#[cfg(feature = "std")]
 #[derive(Debug)]
-pub(crate) struct ByteWriter {
+pub struct WriteSink<W: std::io::Write>(pub W);
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> ByteSink for WriteSink<W> {
+    fn write_bytes(&mut self, bytes: &[u8]) -> Result<(), SerializeError> {
+        self.0.write_all(bytes).map_err(SerializeError::Io)
+    }
+    fn flush(&mut self) -> Result<(), SerializeError> {
+        self.0.flush().map_err(SerializeError::Io)
+    }
+}
+
+/// Writes values of any bit width into a growing byte buffer, see the [module](self) docs
+/// for how bits are packed.
+pub struct ByteWriter<'a> {
     pub(crate) bytes: Vec<u8>,
     pub(crate) pointer: u8,
     pub(crate) index: usize,
+    sink: Option<&'a mut dyn ByteSink>,
+    error: Option<SerializeError>,
+}
+
+impl Default for ByteWriter<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
-impl ByteWriter {
-    pub(crate) fn new() -> Self {
+impl<'a> ByteWriter<'a> {
+    /// Creates an empty [`ByteWriter`].
+    pub fn new() -> Self {
         Self {
             bytes: Vec::new(),
             pointer: 0,
             index: 0,
+            sink: None,
+            error: None,
+        }
+    }
+
+    /// Streams bytes to `sink` as they are completed, instead of keeping them in `bytes`.
+    pub(crate) fn set_sink(&mut self, sink: &'a mut dyn ByteSink) {
+        self.sink = Some(sink);
+    }
+
+    /// Hands complete bytes to the sink once enough have been buffered, keeping a
+    /// partially written last byte. The first sink error is kept and reported by `finish`.
+    fn drain_to_sink(&mut self) {
+        if let Some(sink) = self.sink.as_mut() {
+            let complete = self.bytes.len() - (self.pointer != 0) as usize;
+            if complete < SINK_CHUNK_SIZE {
+                return;
+            }
+            if self.error.is_none() {
+                if let Err(error) = sink.write_bytes(&self.bytes[..complete]) {
+                    self.error = Some(error);
+                }
+            }
+            self.bytes.drain(..complete);
+        }
+    }
+
+    /// Writes everything still buffered to the sink, if any, and flushes it.
+    pub(crate) fn finish(&mut self) -> Result<(), SerializeError> {
+        if let Some(sink) = self.sink.as_mut() {
+            if let Some(error) = self.error.take() {
+                return Err(error);
+            }
+            sink.write_bytes(&self.bytes)?;
+            self.bytes.clear();
+            sink.flush()?;
+        }
+        Ok(())
+    }
+
+    /// The bytes written so far, including a partially written last byte. Bytes already handed
+    /// to a sink are not included.
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Consumes the writer, returning the bytes written so far as in [`ByteWriter::bytes`].
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+
+    /// The number of bits written so far.
+    pub fn bit_position(&self) -> usize {
+        self.index * 8 + self.pointer as usize
+    }
+
+    /// Writes the low `number_of_bits` bits of `value`, least significant bit first. Higher
+    /// bits of `value` are ignored. `number_of_bits` must be at most `32`.
+    pub fn write_bits(&mut self, value: u32, number_of_bits: u8) {
+        debug_assert!(number_of_bits <= 32);
+        let mut written = 0;
+        while written < number_of_bits {
+            let chunk = (number_of_bits - written).min(8);
+            let byte = (value >> written) as u8 & (u8::MAX >> (8 - chunk));
+            self.incomplete_push(byte, chunk);
+            written += chunk;
+        }
+    }
+
+    /// Fills the rest of a partially written byte with zero bits, so that the next write starts
+    /// on a byte boundary. Does nothing if the writer is already aligned.
+    pub fn align_to_byte(&mut self) {
+        if self.pointer != 0 {
+            self.write_bits(0, 8 - self.pointer);
         }
     }
 
@@ -48,6 +190,7 @@ impl ByteWriter {
             self.bytes.push(new_byte);
         }
         self.index += 1;
+        self.drain_to_sink();
     }
     pub(crate) fn incomplete_push(&mut self, byte: u8, number_of_bits: u8) {
         if number_of_bits == 8 {
@@ -78,23 +221,70 @@ impl ByteWriter {
                 self.bytes.push(new_byte);
             }
             self.index += 1;
+            self.drain_to_sink();
         }
     }
 }
 
+/// A source of bits for a [`DeserializeEngine`](super::DeserializeEngine), see the
+/// [module](self) docs for how bits are packed.
 pub trait ByteReader {
-    fn get(&self) -> u8;
-    fn incomplete_get(&self, number_of_bits: u8) -> u8;
-    fn next(&mut self) -> u8; // maybe Option<u8>
-    fn incomplete_next(&mut self, number_of_bits: u8) -> u8;
+    /// Returns the next 8 bits without consuming them.
+    fn get(&self) -> Result<u8, DeserializeError>;
+    /// Returns the next `number_of_bits` bits (at most `8`) without consuming them.
+    fn incomplete_get(&self, number_of_bits: u8) -> Result<u8, DeserializeError>;
+    /// Consumes and returns the next 8 bits.
+    fn next(&mut self) -> Result<u8, DeserializeError>;
+    /// Consumes and returns the next `number_of_bits` bits (at most `8`).
+    fn incomplete_next(&mut self, number_of_bits: u8) -> Result<u8, DeserializeError>;
+    /// The length of the input in bytes.
     fn len(&self) -> usize;
+    /// Returns `true` if the input has no bytes.
     fn is_empty(&self) -> bool;
+    /// Index of the byte the next bit is read from.
     fn index(&self) -> usize;
 
-    #[cfg(feature = "tagging")]
+    /// Position of the next bit to be read.
     fn byte_index(&self) -> super::ByteIndex;
+
+    /// The number of bits read so far.
+    fn bit_position(&self) -> usize {
+        let position = self.byte_index();
+        position.byte * 8 + position.bit as usize
+    }
+
+    /// Consumes the next `number_of_bits` bits and returns them as the low bits of a `u32`,
+    /// least significant bit first. `number_of_bits` must be at most `32`.
+    fn read_bits(&mut self, number_of_bits: u8) -> Result<u32, DeserializeError> {
+        debug_assert!(number_of_bits <= 32);
+        let mut value = 0;
+        let mut read = 0;
+        while read < number_of_bits {
+            let chunk = (number_of_bits - read).min(8);
+            value |= (self.incomplete_next(chunk)? as u32) << read;
+            read += chunk;
+        }
+        Ok(value)
+    }
+
+    /// Skips the rest of a partially read byte, so that the next read starts on a byte
+    /// boundary, and returns the skipped bits. Returns `0` if the reader is already aligned.
+    fn align_to_byte(&mut self) -> Result<u8, DeserializeError> {
+        match self.byte_index().bit {
+            0 => Ok(0),
+            bit => self.incomplete_next(8 - bit),
+        }
+    }
+
+    /// Takes the error that stopped the reader from reading further input, if any, so that
+    /// running out of readable bytes is not mistaken for the end of the input. Readers over
+    /// input of known length never fail and return `None`.
+    fn take_error(&mut self) -> Option<DeserializeError> {
+        None
+    }
 }
 
+/// A [`ByteReader`] over a byte slice.
 #[derive(Debug)]
 pub struct ByteReaderImpl<'a> {
     bytes: &'a [u8],
@@ -103,49 +293,53 @@ pub struct ByteReaderImpl<'a> {
 }
 
 impl<'a> ByteReaderImpl<'a> {
-    pub(crate) fn from(bytes: &'a [u8]) -> Self {
+    /// Creates a reader starting at the first bit of `bytes`.
+    pub fn new(bytes: &'a [u8]) -> Self {
         Self {
             bytes,
             pointer: 0,
             index: 0,
         }
     }
+    pub(crate) fn seek(&mut self, position: super::ByteIndex) {
+        self.index = position.byte;
+        self.pointer = position.bit;
+    }
 }
 
 impl<'a> ByteReader for ByteReaderImpl<'a> {
-    fn get(&self) -> u8 {
-        if self.pointer == 0 {
-            self.bytes[self.index]
-        } else {
-            let mut byte = self.bytes[self.index] >> self.pointer;
-
-            if self.index < self.len() - 1 {
-                let mask = self.bytes[self.index + 1] << (8 - self.pointer);
-                byte |= mask;
-            }
+    fn get(&self) -> Result<u8, DeserializeError> {
+        self.incomplete_get(8)
+    }
+    fn incomplete_get(&self, number_of_bits: u8) -> Result<u8, DeserializeError> {
+        let straddles = self.pointer + number_of_bits > 8;
+        if self.index >= self.len() || (straddles && self.index + 1 >= self.len()) {
+            return Err(DeserializeError::UnexpectedEndOfFile);
+        }
 
-            byte
+        let mut byte = self.bytes[self.index] >> self.pointer;
+        if straddles {
+            byte |= self.bytes[self.index + 1] << (8 - self.pointer);
         }
-    }
-    fn incomplete_get(&self, number_of_bits: u8) -> u8 {
+
         if number_of_bits == 8 {
-            return self.get();
+            return Ok(byte);
         }
-        self.get() << (8 - number_of_bits) >> (8 - number_of_bits)
+        Ok(byte << (8 - number_of_bits) >> (8 - number_of_bits))
     }
-    fn next(&mut self) -> u8 {
-        let byte = self.get();
+    fn next(&mut self) -> Result<u8, DeserializeError> {
+        let byte = self.get()?;
         self.index += 1;
-        byte
+        Ok(byte)
     }
-    fn incomplete_next(&mut self, number_of_bits: u8) -> u8 {
-        let byte = self.incomplete_get(number_of_bits);
+    fn incomplete_next(&mut self, number_of_bits: u8) -> Result<u8, DeserializeError> {
+        let byte = self.incomplete_get(number_of_bits)?;
         self.pointer += number_of_bits;
         if self.pointer >= 8 {
             self.index += 1;
             self.pointer -= 8;
         }
-        byte
+        Ok(byte)
     }
     fn len(&self) -> usize {
         self.bytes.len()
@@ -157,12 +351,12 @@ impl<'a> ByteReader for ByteReaderImpl<'a> {
         self.index
     }
 
-    #[cfg(feature = "tagging")]
     fn byte_index(&self) -> super::ByteIndex {
         super::ByteIndex::new(self.index, self.pointer)
     }
 }
 
+/// A [`ByteReader`] over an iterator of bytes whose total length is known up front.
 #[derive(Debug)]
 pub struct ByteReaderIter<'a, I: Iterator<Item = u8>> {
     iterator: &'a mut I,
@@ -173,6 +367,7 @@ pub struct ByteReaderIter<'a, I: Iterator<Item = u8>> {
 }
 
 impl<'a, I: Iterator<Item = u8>> ByteReaderIter<'a, I> {
+    /// Creates a reader over the first `length` bytes of `iterator`.
     pub fn from(iterator: &'a mut I, length: usize) -> Self {
         let mut reader = Self {
             iterator,
@@ -203,40 +398,40 @@ impl<'a, I: Iterator<Item = u8>> ByteReaderIter<'a, I> {
 }
 
 impl<'a, I: Iterator<Item = u8>> ByteReader for ByteReaderIter<'a, I> {
-    fn get(&self) -> u8 {
-        if self.pointer == 0 {
-            self.buffered_bytes[0]
-        } else {
-            let mut byte = self.buffered_bytes[0] >> self.pointer;
-            if self.index < self.len() - 1 {
-                let mask = self.buffered_bytes[1] << (8 - self.pointer);
-                byte |= mask;
-            }
+    fn get(&self) -> Result<u8, DeserializeError> {
+        self.incomplete_get(8)
+    }
+    fn incomplete_get(&self, number_of_bits: u8) -> Result<u8, DeserializeError> {
+        let straddles = self.pointer + number_of_bits > 8;
+        if self.index >= self.len() || (straddles && self.index + 1 >= self.len()) {
+            return Err(DeserializeError::UnexpectedEndOfFile);
+        }
 
-            byte
+        let mut byte = self.buffered_bytes[0] >> self.pointer;
+        if straddles {
+            byte |= self.buffered_bytes[1] << (8 - self.pointer);
         }
-    }
-    fn incomplete_get(&self, number_of_bits: u8) -> u8 {
+
         if number_of_bits == 8 {
-            return self.get();
+            return Ok(byte);
         }
-        self.get() << (8 - number_of_bits) >> (8 - number_of_bits)
+        Ok(byte << (8 - number_of_bits) >> (8 - number_of_bits))
     }
-    fn next(&mut self) -> u8 {
-        let byte = self.get();
+    fn next(&mut self) -> Result<u8, DeserializeError> {
+        let byte = self.get()?;
         self.index += 1;
         self.proceed_iter();
-        byte
+        Ok(byte)
     }
-    fn incomplete_next(&mut self, number_of_bits: u8) -> u8 {
-        let byte = self.incomplete_get(number_of_bits);
+    fn incomplete_next(&mut self, number_of_bits: u8) -> Result<u8, DeserializeError> {
+        let byte = self.incomplete_get(number_of_bits)?;
         self.pointer += number_of_bits;
         if self.pointer >= 8 {
             self.index += 1;
             self.proceed_iter();
             self.pointer -= 8;
         }
-        byte
+        Ok(byte)
     }
     fn len(&self) -> usize {
         self.length
@@ -248,12 +443,130 @@ impl<'a, I: Iterator<Item = u8>> ByteReader for ByteReaderIter<'a, I> {
         self.index
     }
 
-    #[cfg(feature = "tagging")]
     fn byte_index(&self) -> super::ByteIndex {
         super::ByteIndex::new(self.index, self.pointer)
     }
 }
 
+/// A [`ByteReader`] over any [`std::io::Read`], for sources whose length is not known up
+/// front. Reads one byte at a time, so wrap unbuffered sources in a
+/// [`std::io::BufReader`]. An I/O error is reported as [`DeserializeError::Io`] once the
+/// parser reaches the byte that could not be read.
+#[cfg(feature = "std")]
+#[derive(Debug)]
+pub struct ByteReaderRead<R: std::io::Read> {
+    reader: R,
+    buffered_bytes: [u8; 2],
+    buffered: usize,
+    finished: bool,
+    error: Option<std::io::Error>,
+    pointer: u8,
+    index: usize,
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read> ByteReaderRead<R> {
+    /// Creates a reader over `reader`, reading ahead the first bytes.
+    pub fn new(reader: R) -> Self {
+        let mut reader = Self {
+            reader,
+            buffered_bytes: [0, 0],
+            buffered: 0,
+            finished: false,
+            error: None,
+            pointer: 0,
+            index: 0,
+        };
+        reader.fill_buffer();
+        reader
+    }
+    fn fill_buffer(&mut self) {
+        while self.buffered < 2 && !self.finished {
+            let mut byte = [0];
+            match self.reader.read(&mut byte) {
+                Ok(0) => self.finished = true,
+                Ok(_) => {
+                    self.buffered_bytes[self.buffered] = byte[0];
+                    self.buffered += 1;
+                }
+                Err(error) if error.kind() == std::io::ErrorKind::Interrupted => {}
+                Err(error) => {
+                    self.error = Some(error);
+                    self.finished = true;
+                }
+            }
+        }
+    }
+    fn proceed_buffer(&mut self) {
+        self.buffered_bytes[0] = self.buffered_bytes[1];
+        self.buffered -= 1;
+        self.fill_buffer();
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: std::io::Read> ByteReader for ByteReaderRead<R> {
+    fn get(&self) -> Result<u8, DeserializeError> {
+        self.incomplete_get(8)
+    }
+    fn incomplete_get(&self, number_of_bits: u8) -> Result<u8, DeserializeError> {
+        let straddles = self.pointer + number_of_bits > 8;
+        if self.buffered < 1 + straddles as usize {
+            // Peeking leaves the error in place, so every read at this position reports it.
+            return Err(match &self.error {
+                Some(error) => {
+                    DeserializeError::Io(std::io::Error::new(error.kind(), error.to_string()))
+                }
+                None => DeserializeError::UnexpectedEndOfFile,
+            });
+        }
+
+        let mut byte = self.buffered_bytes[0] >> self.pointer;
+        if straddles {
+            byte |= self.buffered_bytes[1] << (8 - self.pointer);
+        }
+
+        if number_of_bits == 8 {
+            return Ok(byte);
+        }
+        Ok(byte << (8 - number_of_bits) >> (8 - number_of_bits))
+    }
+    fn next(&mut self) -> Result<u8, DeserializeError> {
+        let byte = self.get()?;
+        self.index += 1;
+        self.proceed_buffer();
+        Ok(byte)
+    }
+    fn incomplete_next(&mut self, number_of_bits: u8) -> Result<u8, DeserializeError> {
+        let byte = self.incomplete_get(number_of_bits)?;
+        self.pointer += number_of_bits;
+        if self.pointer >= 8 {
+            self.index += 1;
+            self.proceed_buffer();
+            self.pointer -= 8;
+        }
+        Ok(byte)
+    }
+    /// The number of bytes read so far plus those buffered ahead, as the total is unknown.
+    fn len(&self) -> usize {
+        self.index + self.buffered
+    }
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+    fn index(&self) -> usize {
+        self.index
+    }
+
+    fn byte_index(&self) -> super::ByteIndex {
+        super::ByteIndex::new(self.index, self.pointer)
+    }
+
+    fn take_error(&mut self) -> Option<DeserializeError> {
+        self.error.take().map(DeserializeError::Io)
+    }
+}
+
 pub(crate) fn get_bit(byte: u8, index: u8) -> bool {
     (byte >> (index)) & 0b00000001 == 1
 }
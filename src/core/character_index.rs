@@ -0,0 +1,242 @@
+/*
+ * Copyright 2025 SimplePixelFont
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::core::tables::flagged;
+use crate::core::*;
+use crate::{Map, Vec};
+
+/// A character together with the pixmap it renders with, as found by
+/// [`CharacterIndex`] or [`CharacterTable::resolve`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResolvedCharacter<'a> {
+    /// The character record.
+    pub character: &'a Character,
+    /// Index of the pixmap table within [`Layout::pixmap_tables`].
+    pub pixmap_table_index: usize,
+    /// The pixmap table the pixmap belongs to.
+    pub pixmap_table: &'a PixmapTable,
+    /// Index of the pixmap within [`PixmapTable::pixmaps`].
+    pub pixmap_index: usize,
+    /// The pixmap the character renders with.
+    pub pixmap: &'a Pixmap,
+    /// How far to move right after drawing the character: its
+    /// [`Character::advance_x`] if the table uses it, or the width of its pixmap otherwise.
+    pub advance_x: u8,
+}
+
+impl CharacterTable {
+    /// Finds the pixmap character `index` of this table renders with, following the same
+    /// rules as [`Layout::validate`]: the record's pixmap index defaults to its own index, and
+    /// without a pixmap table index the linked pixmap tables are searched in order. Returns
+    /// `None` if there is no such character, it has no pixmap, or the pixmap's dimensions
+    /// cannot be resolved.
+    pub fn resolve<'a>(
+        &'a self,
+        layout: &'a Layout,
+        index: usize,
+    ) -> Option<ResolvedCharacter<'a>> {
+        let character = self.characters.get(index)?;
+        let links = self.effective_links()?;
+        let pixmap_index = flagged(
+            self.modifier_flags
+                .contains(CharacterTableModifierFlags::UsePixmapIndex),
+            character.pixmap_index,
+        )
+        .map_or(index, usize::from);
+
+        let resolve_in = |link: &u8| {
+            let pixmap_table_index = *link as usize;
+            let pixmap_table = layout.pixmap_tables.get(pixmap_table_index)?;
+            let pixmap = pixmap_table.pixmaps.get(pixmap_index)?;
+            let (width, _, _) = pixmap_table.pixmap_dimensions(pixmap)?;
+            let advance_x = flagged(
+                self.modifier_flags
+                    .contains(CharacterTableModifierFlags::UseAdvanceX),
+                character.advance_x,
+            )
+            .unwrap_or(width);
+
+            Some(ResolvedCharacter {
+                character,
+                pixmap_table_index,
+                pixmap_table,
+                pixmap_index,
+                pixmap,
+                advance_x,
+            })
+        };
+
+        match flagged(
+            self.modifier_flags
+                .contains(CharacterTableModifierFlags::UsePixmapTableIndex),
+            character.pixmap_table_index,
+        ) {
+            Some(pixmap_table_index) => resolve_in(links.get(pixmap_table_index as usize)?),
+            None => links.iter().find_map(resolve_in),
+        }
+    }
+}
+
+/// A lookup table from code points and code point clusters to the glyphs that render them,
+/// built once from one or more [`CharacterTable`]s.
+///
+/// Lookups are constant time with the `std` feature, and logarithmic without it. When
+/// several characters map the same code points, the first one added wins, and characters
+/// without a resolvable pixmap are left out.
+#[derive(Debug, Clone, Default)]
+pub struct CharacterIndex<'a> {
+    code_points: Map<char, ResolvedCharacter<'a>>,
+    clusters: Map<&'a str, ResolvedCharacter<'a>>,
+    longest_cluster: usize,
+}
+
+/// One glyph of a string split by [`CharacterIndex::segment`].
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GlyphRef<'a, 't> {
+    /// The part of the input this glyph renders, one or more code points.
+    pub text: &'t str,
+    /// Byte offset of `text` within the input.
+    pub offset: usize,
+    /// The character to draw, or `None` if `text` is not mapped and there is no replacement.
+    pub glyph: Option<ResolvedCharacter<'a>>,
+    /// Whether `text` is a single code point the index does not map, so `glyph` is the
+    /// replacement glyph, if any.
+    pub is_replacement: bool,
+}
+
+impl<'a> CharacterIndex<'a> {
+    /// Indexes every character of the character tables at `character_tables`, in order.
+    /// Indexes without a character table are skipped, as are characters whose pixmap does
+    /// not resolve (see [`CharacterTable::resolve`]), so looking those up returns `None`.
+    /// [`Layout::resolve_font`] reports such characters as [`ResolveError::MissingPixmap`]
+    /// instead.
+    pub fn from_tables(
+        layout: &'a Layout,
+        character_tables: impl IntoIterator<Item = usize>,
+    ) -> Self {
+        let mut index = Self::default();
+        for table in character_tables {
+            let Some(table) = layout.character_tables.get(table) else {
+                continue;
+            };
+            for record in 0..table.characters.len() {
+                if let Some(resolved) = table.resolve(layout, record) {
+                    index.insert(resolved);
+                }
+            }
+        }
+        index
+    }
+
+    /// Indexes the character tables linked by font `font_index` of font table
+    /// `font_table_index`, following [`Font::linked_character_table_indexes`] through the
+    /// font table's links. Returns `None` if there is no such font.
+    pub fn for_font(
+        layout: &'a Layout,
+        font_table_index: usize,
+        font_index: usize,
+    ) -> Option<Self> {
+        let font_table = layout.font_tables.get(font_table_index)?;
+        let font = font_table.fonts.get(font_index)?;
+        let links = font_table.effective_links().unwrap_or_default();
+
+        Some(Self::from_tables(
+            layout,
+            font.linked_character_table_indexes
+                .iter()
+                .filter_map(|linked| links.get(*linked as usize))
+                .map(|link| *link as usize),
+        ))
+    }
+
+    fn insert(&mut self, resolved: ResolvedCharacter<'a>) {
+        let cluster = resolved.character.code_points.as_str();
+        let mut chars = cluster.chars();
+        if let (Some(code_point), None) = (chars.next(), chars.next()) {
+            self.code_points.entry(code_point).or_insert(resolved);
+        }
+        self.clusters.entry(cluster).or_insert(resolved);
+        self.longest_cluster = self.longest_cluster.max(cluster.chars().count());
+    }
+
+    /// Finds the character that maps exactly the single `code_point`. Takes constant time
+    /// with the `std` feature, where the index is a hash map, and logarithmic time without
+    /// it, where it is a `BTreeMap`.
+    pub fn get(&self, code_point: char) -> Option<ResolvedCharacter<'a>> {
+        self.code_points.get(&code_point).copied()
+    }
+
+    /// Finds the character that maps exactly the code points of `cluster`, such as a
+    /// ligature. Also finds single code point characters. Takes the same time as
+    /// [`CharacterIndex::get`], plus hashing or comparing `cluster`.
+    pub fn get_cluster(&self, cluster: &str) -> Option<ResolvedCharacter<'a>> {
+        self.clusters.get(cluster).copied()
+    }
+
+    /// Splits `text` into the glyphs that render it. At each position the longest indexed
+    /// cluster is matched first, so ligatures such as `"fi"` or flag emoji mapped as a pair of
+    /// regional indicators win over their single code points. A code point the index does not
+    /// map is rendered with the glyph for `replacement`, if given and mapped.
+    pub fn segment<'t>(&self, text: &'t str, replacement: Option<char>) -> Vec<GlyphRef<'a, 't>> {
+        let replacement = replacement.and_then(|code_point| self.get(code_point));
+        let mut glyphs = Vec::new();
+        let mut offset = 0;
+
+        while offset < text.len() {
+            let rest = &text[offset..];
+            // The longest candidate ends after as many code points as the longest cluster.
+            let end = rest
+                .char_indices()
+                .nth(self.longest_cluster.max(1))
+                .map_or(rest.len(), |(end, _)| end);
+            let single = rest.chars().next().map_or(rest.len(), char::len_utf8);
+
+            let glyph = core::iter::once(end)
+                .chain(rest[..end].char_indices().rev().map(|(start, _)| start))
+                .take_while(|length| *length > 0)
+                .find_map(|length| {
+                    let cluster = &rest[..length];
+                    self.get_cluster(cluster).map(|resolved| GlyphRef {
+                        text: cluster,
+                        offset,
+                        glyph: Some(resolved),
+                        is_replacement: false,
+                    })
+                })
+                .unwrap_or_else(|| GlyphRef {
+                    text: &rest[..single],
+                    offset,
+                    glyph: replacement,
+                    is_replacement: true,
+                });
+            offset += glyph.text.len();
+            glyphs.push(glyph);
+        }
+        glyphs
+    }
+
+    /// The number of distinct code point clusters indexed.
+    pub fn len(&self) -> usize {
+        self.clusters.len()
+    }
+
+    /// Returns `true` if no character was indexed.
+    pub fn is_empty(&self) -> bool {
+        self.clusters.is_empty()
+    }
+}
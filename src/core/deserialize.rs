@@ -22,12 +22,14 @@ impl<'a, T: TagWriter> DeserializeEngine<'a, ByteReaderImpl<'a>, T> {
     /// Creates a [`DeserializeEngine`] reading from a byte slice, recording tags with `tags`.
     pub fn from_data_and_tags(data: &'a [u8], tags: T) -> Self {
         Self {
-            bytes: byte::ByteReaderImpl::from(data),
+            bytes: byte::ByteReaderImpl::new(data),
             layout: Layout::default(),
+            options: DeserializeOptions::default(),
             #[cfg(feature = "tagging")]
             tags,
-            #[cfg(feature = "tagging")]
             tagging_data: TaggingData::default(),
+            pixmap_bytes: 0,
+            error_start: None,
             _phantom: PhantomData,
             _phantom2: &PhantomData,
         }
@@ -43,12 +45,14 @@ impl<'a> DeserializeEngine<'a, ByteReaderImpl<'a>> {
     /// Creates a [`DeserializeEngine`] reading from a byte slice, without tag tracking.
     pub fn from_data(data: &'a [u8]) -> Self {
         Self {
-            bytes: byte::ByteReaderImpl::from(data),
+            bytes: byte::ByteReaderImpl::new(data),
             layout: Layout::default(),
+            options: DeserializeOptions::default(),
             #[cfg(feature = "tagging")]
             tags: TagWriterNoOp,
-            #[cfg(feature = "tagging")]
             tagging_data: TaggingData::default(),
+            pixmap_bytes: 0,
+            error_start: None,
             _phantom: PhantomData,
             _phantom2: &PhantomData,
         }
@@ -61,28 +65,112 @@ impl<'a, R: ByteReader> DeserializeEngine<'a, R> {
         Self {
             bytes: reader,
             layout: Layout::default(),
+            options: DeserializeOptions::default(),
             #[cfg(feature = "tagging")]
             tags: TagWriterNoOp,
-            #[cfg(feature = "tagging")]
             tagging_data: TaggingData::default(),
+            pixmap_bytes: 0,
+            error_start: None,
             _phantom: PhantomData,
             _phantom2: &PhantomData,
         }
     }
 }
 
+impl<R: ByteReader, T: TagWriter> DeserializeEngine<'_, R, T> {
+    /// Wraps `error` with where it occurred and the table/record being read. The position is
+    /// the start of the field that failed to decode if one was recorded with
+    /// [`DeserializeEngine::error_at`], and otherwise the position reading stopped at.
+    pub(crate) fn error_context(&self, error: DeserializeError) -> DeserializeErrorContext {
+        let table_type = self.tagging_data.current_table_type;
+        DeserializeErrorContext {
+            error,
+            byte_index: self.error_start.unwrap_or_else(|| self.bytes.byte_index()),
+            table_type,
+            table_index: table_type.map(|_| self.tagging_data.current_table_index),
+            record_index: (table_type.is_some() && self.tagging_data.in_record)
+                .then_some(self.tagging_data.current_record_index),
+        }
+    }
+
+    /// Records `start`, the position of the field being read, as where `error` occurred.
+    pub(crate) fn error_at(
+        &mut self,
+        start: ByteIndex,
+        error: DeserializeError,
+    ) -> DeserializeError {
+        self.error_start = Some(start);
+        error
+    }
+
+    /// Reads a byte and converts it with `decode`, reporting a failed conversion at that byte.
+    pub(crate) fn next_decoded<V>(
+        &mut self,
+        decode: impl FnOnce(u8) -> Result<V, DeserializeError>,
+    ) -> Result<V, DeserializeError> {
+        let start = self.bytes.byte_index();
+        let value = self.bytes.next()?;
+        decode(value).map_err(|error| self.error_at(start, error))
+    }
+
+    /// Fails with [`DeserializeError::LimitExceeded`] if `value` is above the configured
+    /// [`Limits`] entry for `which`. Limits are checked before reading what they bound, so
+    /// the error is reported at the current position.
+    pub(crate) fn check_limit(
+        &mut self,
+        which: Limit,
+        value: usize,
+    ) -> Result<(), DeserializeError> {
+        let limits = &self.options.limits;
+        let maximum = match which {
+            Limit::Tables => limits.max_tables,
+            Limit::PixmapBytes => limits.max_pixmap_bytes,
+            Limit::CodePoints => limits.max_code_points,
+            Limit::StringLength => limits.max_string_length,
+        };
+        match maximum {
+            Some(maximum) if value > maximum => {
+                let start = self.bytes.byte_index();
+                Err(self.error_at(start, DeserializeError::LimitExceeded { which }))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns `true` if the version being read can express `feature`.
+    pub(crate) fn supports(&self, feature: VersionFeature) -> bool {
+        self.layout.version.supports(feature)
+    }
+
+    /// Returns `true` if a table starting with `identifier` is an extension table, read as a
+    /// [`RawTable`], rather than one this crate decodes.
+    pub(crate) fn is_extension_table(&self, identifier: u8) -> bool {
+        identifier >= RawTable::MIN_IDENTIFIER && self.supports(VersionFeature::ExtensionTables)
+    }
+
+    /// Reads a flags byte whose defined bits are `known`. Any other set bit is rejected with
+    /// [`DeserializeError::ReservedBitsSet`] in strict mode, and in versions supporting
+    /// [`VersionFeature::ZeroReservedBits`].
+    pub(crate) fn next_flags(&mut self, known: u8) -> Result<u8, DeserializeError> {
+        let reserved_checked =
+            self.options.strict || self.supports(VersionFeature::ZeroReservedBits);
+        self.next_decoded(|value| {
+            if reserved_checked && value & !known != 0 {
+                return Err(DeserializeError::ReservedBitsSet { value });
+            }
+            Ok(value)
+        })
+    }
+}
+
 pub(crate) fn next_signature<R: ByteReader, T: TagWriter>(
     engine: &mut DeserializeEngine<R, T>,
 ) -> Result<(), DeserializeError> {
-    if engine.bytes.index() + 4 > engine.bytes.len() {
-        return Err(DeserializeError::UnexpectedEndOfFile);
-    }
-    #[cfg(feature = "tagging")]
     let start = engine.bytes.byte_index();
 
     for byte in [127, 102, 115, 70].iter() {
-        if engine.bytes.next() != *byte {
-            return Err(DeserializeError::InvalidSignature);
+        if engine.bytes.next()? != *byte {
+            return Err(engine.error_at(start, DeserializeError::InvalidSignature));
         }
     }
 
@@ -98,8 +186,7 @@ pub(crate) fn next_signature<R: ByteReader, T: TagWriter>(
 pub(crate) fn next_version<R: ByteReader, T: TagWriter>(
     engine: &mut DeserializeEngine<R, T>,
 ) -> Result<(), DeserializeError> {
-    let version = engine.bytes.next();
-    let version = Version::try_from(version)?;
+    let version = engine.next_decoded(Version::try_from)?;
     #[cfg(feature = "tagging")]
     engine.tags.tag_byte(
         TagKind::Version { value: version },
@@ -113,7 +200,7 @@ pub(crate) fn next_version<R: ByteReader, T: TagWriter>(
 pub(crate) fn next_header<R: ByteReader, T: TagWriter>(
     engine: &mut DeserializeEngine<R, T>,
 ) -> Result<(), DeserializeError> {
-    let file_properties = engine.bytes.next();
+    let file_properties = engine.next_flags(0b00000001)?;
 
     engine.layout.compact = byte::get_bit(file_properties, 0);
 
@@ -128,3 +215,106 @@ pub(crate) fn next_header<R: ByteReader, T: TagWriter>(
 
     Ok(())
 }
+
+pub(crate) fn next_trailing_data<R: ByteReader, T: TagWriter>(
+    engine: &mut DeserializeEngine<R, T>,
+) -> Result<(), DeserializeError> {
+    // The rest of a partially read byte is padding, and must be left zeroed.
+    if engine.bytes.align_to_byte()? != 0 {
+        return Err(DeserializeError::TrailingData);
+    }
+
+    if engine.bytes.index() < engine.bytes.len() {
+        return Err(DeserializeError::TrailingData);
+    }
+    Ok(())
+}
+
+/// Reads the identifier of every table following the header and hands its [`TableEntry`] to
+/// `next_table`, which reads or skips the table's body. The tables are checked against
+/// [`Limit::Tables`], and the end of the file against the engine's [`DeserializeOptions`].
+pub(crate) fn next_tables<R: ByteReader, T: TagWriter>(
+    engine: &mut DeserializeEngine<R, T>,
+    mut next_table: impl FnMut(&mut DeserializeEngine<R, T>, TableEntry) -> Result<(), DeserializeError>,
+) -> Result<(), DeserializeError> {
+    let mut table_types = Vec::new();
+    // Stop once less than an identifier byte is left, which is the padding after the last
+    // table of a compact layout. A byte or more is always read as another table.
+    while engine.bytes.bit_position() + 8 <= engine.bytes.len() * 8 {
+        engine.tagging_data.current_table_type = None;
+        engine.check_limit(Limit::Tables, table_types.len() + 1)?;
+
+        let offset = engine.bytes.byte_index();
+        let identifier = engine.bytes.next()?;
+        let table_type = if engine.is_extension_table(identifier) {
+            None
+        } else {
+            Some(
+                match TableIdentifier::try_from(identifier)
+                    .map_err(|error| engine.error_at(offset, error))?
+                {
+                    TableIdentifier::Character => TableType::Character,
+                    TableIdentifier::Pixmap => TableType::Pixmap,
+                    TableIdentifier::Color => TableType::Color,
+                    TableIdentifier::Font => TableType::Font,
+                },
+            )
+        };
+        let index = table_types
+            .iter()
+            .filter(|other| **other == table_type)
+            .count();
+        table_types.push(table_type);
+
+        if let Some(table_type) = table_type {
+            engine.tagging_data.begin_table(table_type, index);
+        }
+        next_table(
+            engine,
+            TableEntry {
+                identifier,
+                table_type,
+                index,
+                offset,
+            },
+        )?;
+    }
+
+    engine.tagging_data.current_table_type = None;
+    if let Some(error) = engine.bytes.take_error() {
+        return Err(error);
+    }
+    if engine.options.strict {
+        next_trailing_data(engine)?;
+    }
+    Ok(())
+}
+
+pub(crate) fn next_raw_table<R: ByteReader, T: TagWriter>(
+    engine: &mut DeserializeEngine<R, T>,
+    identifier: u8,
+) -> Result<RawTable, DeserializeError> {
+    #[cfg(feature = "tagging")]
+    let start = engine.bytes.byte_index();
+    #[cfg(feature = "tagging")]
+    let start = ByteIndex::new(start.byte - 1, start.bit);
+
+    let length = engine.bytes.read_bits(32)? as usize;
+
+    // The length is untrusted, so the body is not preallocated.
+    let mut data = Vec::new();
+    for _ in 0..length {
+        data.push(engine.bytes.next()?);
+    }
+
+    #[cfg(feature = "tagging")]
+    engine.tags.tag_span(
+        TagKind::RawTable {
+            index: engine.layout.unknown_tables.len() as u8,
+            identifier,
+        },
+        Span::new(start, engine.bytes.byte_index()),
+    );
+
+    Ok(RawTable::new(identifier, data))
+}
@@ -0,0 +1,377 @@
+/*
+ * Copyright 2025 SimplePixelFont
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::core::byte::ByteReader;
+use crate::core::tables::pixmap::{next_bits_per_pixel, next_height, next_pixmap, next_width};
+use crate::core::*;
+use crate::Vec;
+
+/// Where one table of a `.spf` file starts, as recorded by [`TableDirectory::scan`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TableEntry {
+    /// The identifier byte of the table.
+    pub identifier: u8,
+    /// The kind of table, or `None` for an extension table (see [`RawTable`]).
+    pub table_type: Option<TableType>,
+    /// Index of the table among the tables of the same kind, as in the [`Layout`] vectors.
+    pub index: usize,
+    /// Position of the table's identifier byte.
+    pub offset: ByteIndex,
+}
+
+/// A list of the tables in a `.spf` buffer and where each one starts, so that single
+/// tables or pixmaps can be decoded on demand instead of parsing the whole file.
+#[derive(Debug, Clone)]
+pub struct TableDirectory<'a> {
+    buffer: &'a [u8],
+    version: Version,
+    compact: bool,
+    entries: Vec<TableEntry>,
+}
+
+impl<'a> TableDirectory<'a> {
+    /// Walks every table in `buffer` once, recording its [`TableEntry`]. Records are stepped
+    /// over without being decoded or kept, so malformed record contents are only reported
+    /// when their table is decoded.
+    pub fn scan(buffer: &'a [u8]) -> Result<Self, DeserializeErrorContext> {
+        let mut engine = DeserializeEngine::from_data(buffer);
+        let mut entries = Vec::new();
+        scan_tables(&mut engine, &mut entries).map_err(|error| engine.error_context(error))?;
+
+        Ok(Self {
+            buffer,
+            version: engine.layout.version,
+            compact: engine.layout.compact,
+            entries,
+        })
+    }
+
+    /// The format version read from the file header.
+    pub fn version(&self) -> Version {
+        self.version
+    }
+
+    /// Whether the file is compact, see [`Layout::compact`].
+    pub fn compact(&self) -> bool {
+        self.compact
+    }
+
+    /// Every table in the file, in file order.
+    pub fn entries(&self) -> &[TableEntry] {
+        &self.entries
+    }
+
+    /// Finds the entry of the `index`th table of kind `table_type`.
+    pub fn entry(&self, table_type: TableType, index: usize) -> Option<&TableEntry> {
+        self.entries
+            .iter()
+            .find(|entry| entry.table_type == Some(table_type) && entry.index == index)
+    }
+
+    /// Creates an engine positioned just after the identifier of `entry`'s table.
+    fn engine_at(&self, entry: &TableEntry) -> DeserializeEngine<'a> {
+        let mut engine = DeserializeEngine::from_data(self.buffer);
+        engine.layout.version = self.version;
+        engine.layout.compact = self.compact;
+        engine
+            .bytes
+            .seek(ByteIndex::new(entry.offset.byte + 1, entry.offset.bit));
+        if let Some(table_type) = entry.table_type {
+            engine.tagging_data.begin_table(table_type, entry.index);
+        }
+        engine
+    }
+
+    /// Decodes the `index`th table of kind `table_type`, or returns `None` if there is no
+    /// such table.
+    fn table<T: Table + Default>(
+        &self,
+        table_type: TableType,
+        index: usize,
+    ) -> Result<Option<T>, DeserializeErrorContext> {
+        let Some(entry) = self.entry(table_type, index) else {
+            return Ok(None);
+        };
+        let mut engine = self.engine_at(entry);
+        let mut table = T::default();
+        table
+            .deserialize(&mut engine)
+            .map_err(|error| engine.error_context(error))?;
+        Ok(Some(table))
+    }
+
+    /// Decodes the character table at `index`, or returns `None` if there is no such table.
+    /// Fails if the table cannot be decoded.
+    pub fn character_table(
+        &self,
+        index: usize,
+    ) -> Result<Option<CharacterTable>, DeserializeErrorContext> {
+        self.table(TableType::Character, index)
+    }
+
+    /// Decodes the pixmap table at `index`, or returns `None` if there is no such table.
+    /// Fails if the table cannot be decoded.
+    pub fn pixmap_table(
+        &self,
+        index: usize,
+    ) -> Result<Option<PixmapTable>, DeserializeErrorContext> {
+        self.table(TableType::Pixmap, index)
+    }
+
+    /// Decodes the color table at `index`, or returns `None` if there is no such table.
+    /// Fails if the table cannot be decoded.
+    pub fn color_table(&self, index: usize) -> Result<Option<ColorTable>, DeserializeErrorContext> {
+        self.table(TableType::Color, index)
+    }
+
+    /// Decodes the font table at `index`, or returns `None` if there is no such table.
+    /// Fails if the table cannot be decoded.
+    pub fn font_table(&self, index: usize) -> Result<Option<FontTable>, DeserializeErrorContext> {
+        self.table(TableType::Font, index)
+    }
+
+    /// Decodes only pixmap `pixmap_index` of pixmap table `table_index`, stepping over the
+    /// pixmaps before it without decoding them. Returns `None` if there is no such pixmap,
+    /// and fails if it or the records before it cannot be read.
+    pub fn pixmap(
+        &self,
+        table_index: usize,
+        pixmap_index: usize,
+    ) -> Result<Option<Pixmap>, DeserializeErrorContext> {
+        let Some(entry) = self.entry(TableType::Pixmap, table_index) else {
+            return Ok(None);
+        };
+        let mut engine = self.engine_at(entry);
+        next_pixmap_at(&mut engine, pixmap_index).map_err(|error| engine.error_context(error))
+    }
+}
+
+/// Steps over `count` bytes.
+fn skip_bytes<R: ByteReader, T: TagWriter>(
+    engine: &mut DeserializeEngine<R, T>,
+    count: usize,
+) -> Result<(), DeserializeError> {
+    for _ in 0..count {
+        engine.bytes.next()?;
+    }
+    Ok(())
+}
+
+/// Steps over `count` bits, which may end partway through a byte in compact layouts.
+fn skip_bits<R: ByteReader, T: TagWriter>(
+    engine: &mut DeserializeEngine<R, T>,
+    count: usize,
+) -> Result<(), DeserializeError> {
+    skip_bytes(engine, count / 8)?;
+    match (count % 8) as u8 {
+        0 => Ok(()),
+        remainder => engine.bytes.incomplete_next(remainder).map(|_| ()),
+    }
+}
+
+/// Steps over one UTF-8 encoded character, using its first byte to tell how many bytes
+/// follow.
+fn skip_character<R: ByteReader, T: TagWriter>(
+    engine: &mut DeserializeEngine<R, T>,
+) -> Result<(), DeserializeError> {
+    let invalid = DeserializeError::InvalidUtf8 {
+        table: engine.tagging_data.current_table_index,
+        record: engine.tagging_data.current_record_index,
+    };
+    let continuation_bytes = engine.next_decoded(|lead| match lead.leading_ones() {
+        0 => Ok(0),
+        2 => Ok(1),
+        3 => Ok(2),
+        4 => Ok(3),
+        _ => Err(invalid),
+    })?;
+    skip_bytes(engine, continuation_bytes)
+}
+
+/// Steps over a string of at least one character followed by a null byte, or of exactly
+/// `length` characters if it is given.
+fn skip_string<R: ByteReader, T: TagWriter>(
+    engine: &mut DeserializeEngine<R, T>,
+    length: Option<u8>,
+) -> Result<(), DeserializeError> {
+    let mut characters = 0;
+    loop {
+        skip_character(engine)?;
+        characters += 1;
+        match length {
+            Some(length) if characters == length as usize => return Ok(()),
+            Some(_) => {}
+            None if engine.bytes.get()? == 0 => return engine.bytes.next().map(|_| ()),
+            None => {}
+        }
+    }
+}
+
+/// Reads the dimensions of a pixmap and steps over its data, returning the dimensions in
+/// `pixmap`.
+fn skip_pixmap<R: ByteReader, T: TagWriter>(
+    engine: &mut DeserializeEngine<R, T>,
+    table: &PixmapTable,
+    pixmap: &mut Pixmap,
+) -> Result<(), DeserializeError> {
+    next_width(engine, pixmap, table.constant_width)?;
+    next_height(engine, pixmap, table.constant_height)?;
+    next_bits_per_pixel(engine, pixmap, table.constant_bits_per_pixel)?;
+
+    // The dimensions were just read, wherever they are stored.
+    let (width, height, bits_per_pixel) = table.pixmap_dimensions(pixmap).unwrap();
+    let total_bits = width as usize * height as usize * bits_per_pixel as usize;
+    if engine.layout.compact {
+        skip_bits(engine, total_bits)
+    } else {
+        skip_bits(engine, total_bits.div_ceil(8) * 8)
+    }
+}
+
+fn skip_character_table<R: ByteReader, T: TagWriter>(
+    engine: &mut DeserializeEngine<R, T>,
+) -> Result<(), DeserializeError> {
+    let mut table = CharacterTable::default();
+    table.next_modifer_flags(engine)?;
+    table.next_configurations(engine)?;
+    table.next_table_links(engine)?;
+
+    let mut character = Character::default();
+    for index in 0..engine.bytes.next()? {
+        engine.tagging_data.current_record_index = index;
+        engine.tagging_data.in_record = true;
+        table.next_character_modifiers(engine, &mut character)?;
+        skip_string(engine, table.constant_code_point_count)?;
+    }
+    Ok(())
+}
+
+fn skip_pixmap_table<R: ByteReader, T: TagWriter>(
+    engine: &mut DeserializeEngine<R, T>,
+) -> Result<(), DeserializeError> {
+    let mut table = PixmapTable::default();
+    table.next_modifier_flags(engine)?;
+    table.next_configurations(engine)?;
+    table.next_table_links(engine)?;
+
+    let mut pixmap = Pixmap::default();
+    for index in 0..engine.bytes.next()? {
+        engine.tagging_data.current_record_index = index;
+        engine.tagging_data.in_record = true;
+        skip_pixmap(engine, &table, &mut pixmap)?;
+    }
+    Ok(())
+}
+
+fn skip_color_table<R: ByteReader, T: TagWriter>(
+    engine: &mut DeserializeEngine<R, T>,
+) -> Result<(), DeserializeError> {
+    let mut table = ColorTable::default();
+    table.next_modifer_flags(engine)?;
+    table.next_configurations(engine)?;
+    table.next_table_links(engine)?;
+
+    // Every color is its red, green and blue bytes, after its color type and alpha if the
+    // table does not set them once for all colors.
+    let color_bytes = 3
+        + table
+            .modifier_flags
+            .contains(ColorTableModifierFlags::UseColorType) as usize
+        + table.constant_alpha.is_none() as usize;
+    let color_count = engine.bytes.next()? as usize;
+    skip_bytes(engine, color_count * color_bytes)
+}
+
+fn skip_font_table<R: ByteReader, T: TagWriter>(
+    engine: &mut DeserializeEngine<R, T>,
+) -> Result<(), DeserializeError> {
+    let mut table = FontTable::default();
+    table.next_modifer_flags(engine)?;
+    table.next_configurations(engine)?;
+    table.next_table_links(engine)?;
+
+    for index in 0..engine.bytes.next()? {
+        engine.tagging_data.current_record_index = index;
+        engine.tagging_data.in_record = true;
+        // Name and author, then the version and font type bytes.
+        skip_string(engine, None)?;
+        skip_string(engine, None)?;
+        skip_bytes(engine, 2)?;
+        let linked_character_tables = engine.bytes.next()? as usize;
+        skip_bytes(engine, linked_character_tables)?;
+    }
+    Ok(())
+}
+
+fn next_pixmap_at(
+    engine: &mut DeserializeEngine,
+    pixmap_index: usize,
+) -> Result<Option<Pixmap>, DeserializeError> {
+    let mut pixmap_table = PixmapTable::default();
+    pixmap_table.next_modifier_flags(engine)?;
+    pixmap_table.next_configurations(engine)?;
+    pixmap_table.next_table_links(engine)?;
+
+    let pixmap_count = engine.bytes.next()?;
+    if pixmap_index >= pixmap_count as usize {
+        return Ok(None);
+    }
+
+    let mut pixmap = Pixmap::default();
+    for index in 0..=pixmap_index as u8 {
+        engine.tagging_data.current_record_index = index;
+        engine.tagging_data.in_record = true;
+
+        if index < pixmap_index as u8 {
+            skip_pixmap(engine, &pixmap_table, &mut pixmap)?;
+            continue;
+        }
+        next_width(engine, &mut pixmap, pixmap_table.constant_width)?;
+        next_height(engine, &mut pixmap, pixmap_table.constant_height)?;
+        next_bits_per_pixel(engine, &mut pixmap, pixmap_table.constant_bits_per_pixel)?;
+        next_pixmap(
+            engine,
+            &mut pixmap,
+            pixmap_table.constant_width,
+            pixmap_table.constant_height,
+            pixmap_table.constant_bits_per_pixel,
+        )?;
+    }
+    Ok(Some(pixmap))
+}
+
+fn scan_tables(
+    engine: &mut DeserializeEngine,
+    entries: &mut Vec<TableEntry>,
+) -> Result<(), DeserializeError> {
+    deserialize::next_signature(engine)?;
+    deserialize::next_version(engine)?;
+    deserialize::next_header(engine)?;
+
+    deserialize::next_tables(engine, |engine, entry| {
+        entries.push(entry);
+        match entry.table_type {
+            None => {
+                let length = engine.bytes.read_bits(32)? as usize;
+                skip_bytes(engine, length)
+            }
+            Some(TableType::Character) => skip_character_table(engine),
+            Some(TableType::Pixmap) => skip_pixmap_table(engine),
+            Some(TableType::Color) => skip_color_table(engine),
+            Some(TableType::Font) => skip_font_table(engine),
+        }
+    })
+}
@@ -0,0 +1,493 @@
+/*
+ * Copyright 2025 SimplePixelFont
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::core::tables::character::next_code_points;
+use crate::core::tables::font::{
+    next_author, next_font_type, next_linked_character_table_indexes, next_name, next_version,
+};
+use crate::core::tables::pixmap::{next_bits_per_pixel, next_height, next_pixmap, next_width};
+use crate::core::*;
+use crate::{ToOwned, Vec};
+
+/// A view of a `.spf` file that borrows pixmap data, code points and font strings straight
+/// from the input buffer instead of copying them, as returned by [`layout_ref_from_data`].
+///
+/// Only byte-aligned files (where [`Layout::compact`] is `false`) can be borrowed from.
+/// Table and record fields mirror their owned counterparts in [`Layout`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct LayoutRef<'a> {
+    /// See [`Layout::version`].
+    pub version: Version,
+    /// See [`Layout::character_tables`].
+    pub character_tables: Vec<CharacterTableRef<'a>>,
+    /// See [`Layout::color_tables`]. Color tables hold no variable length data, so they are
+    /// not borrowed.
+    pub color_tables: Vec<ColorTable>,
+    /// See [`Layout::pixmap_tables`].
+    pub pixmap_tables: Vec<PixmapTableRef<'a>>,
+    /// See [`Layout::font_tables`].
+    pub font_tables: Vec<FontTableRef<'a>>,
+    /// See [`Layout::unknown_tables`].
+    pub unknown_tables: Vec<RawTableRef<'a>>,
+}
+
+/// A borrowed [`CharacterTable`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CharacterTableRef<'a> {
+    /// See [`CharacterTable::modifier_flags`].
+    pub modifier_flags: CharacterTableModifierFlags,
+    /// See [`CharacterTable::configuration_flags`].
+    pub configuration_flags: CharacterTableConfigurationFlags,
+    /// See [`CharacterTable::constant_code_point_count`].
+    pub constant_code_point_count: Option<u8>,
+    /// See [`CharacterTable::link_flags`].
+    pub link_flags: CharacterTableLinkFlags,
+    /// See [`CharacterTable::pixmap_table_indexes`].
+    pub pixmap_table_indexes: Option<Vec<u8>>,
+    /// See [`CharacterTable::characters`].
+    pub characters: Vec<CharacterRef<'a>>,
+}
+
+/// A borrowed [`Character`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct CharacterRef<'a> {
+    /// See [`Character::advance_x`].
+    pub advance_x: Option<u8>,
+    /// See [`Character::pixmap_index`].
+    pub pixmap_index: Option<u8>,
+    /// See [`Character::pixmap_table_index`].
+    pub pixmap_table_index: Option<u8>,
+    /// See [`Character::code_points`].
+    pub code_points: &'a str,
+}
+
+/// A borrowed [`PixmapTable`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PixmapTableRef<'a> {
+    /// See [`PixmapTable::configuration_flags`].
+    pub configuration_flags: PixmapTableConfigurationFlags,
+    /// See [`PixmapTable::constant_width`].
+    pub constant_width: Option<u8>,
+    /// See [`PixmapTable::constant_height`].
+    pub constant_height: Option<u8>,
+    /// See [`PixmapTable::constant_bits_per_pixel`].
+    pub constant_bits_per_pixel: Option<u8>,
+    /// See [`PixmapTable::link_flags`].
+    pub link_flags: PixmapTableLinkFlags,
+    /// See [`PixmapTable::color_table_indexes`].
+    pub color_table_indexes: Option<Vec<u8>>,
+    /// See [`PixmapTable::pixmaps`].
+    pub pixmaps: Vec<PixmapRef<'a>>,
+}
+
+/// A borrowed [`Pixmap`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PixmapRef<'a> {
+    /// See [`Pixmap::custom_width`].
+    pub custom_width: Option<u8>,
+    /// See [`Pixmap::custom_height`].
+    pub custom_height: Option<u8>,
+    /// See [`Pixmap::custom_bits_per_pixel`].
+    pub custom_bits_per_pixel: Option<u8>,
+    /// See [`Pixmap::data`].
+    pub data: &'a [u8],
+}
+
+/// A borrowed [`FontTable`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FontTableRef<'a> {
+    /// See [`FontTable::link_flags`].
+    pub link_flags: FontTableLinkFlags,
+    /// See [`FontTable::character_table_indexes`].
+    pub character_table_indexes: Option<Vec<u8>>,
+    /// See [`FontTable::fonts`].
+    pub fonts: Vec<FontRef<'a>>,
+}
+
+/// A borrowed [`Font`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FontRef<'a> {
+    /// See [`Font::name`].
+    pub name: &'a str,
+    /// See [`Font::author`].
+    pub author: &'a str,
+    /// See [`Font::version`].
+    pub version: u8,
+    /// See [`Font::font_type`].
+    pub font_type: FontType,
+    /// See [`Font::linked_character_table_indexes`].
+    pub linked_character_table_indexes: Vec<u8>,
+}
+
+/// A borrowed [`RawTable`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RawTableRef<'a> {
+    /// See [`RawTable::identifier`].
+    pub identifier: u8,
+    /// See [`RawTable::data`].
+    pub data: &'a [u8],
+}
+
+impl LayoutRef<'_> {
+    /// Copies every borrowed field, producing the equivalent owned [`Layout`].
+    pub fn to_owned(&self) -> Layout {
+        Layout {
+            version: self.version,
+            compact: false,
+            character_tables: self
+                .character_tables
+                .iter()
+                .map(CharacterTableRef::to_owned)
+                .collect(),
+            color_tables: self.color_tables.clone(),
+            pixmap_tables: self
+                .pixmap_tables
+                .iter()
+                .map(PixmapTableRef::to_owned)
+                .collect(),
+            font_tables: self
+                .font_tables
+                .iter()
+                .map(FontTableRef::to_owned)
+                .collect(),
+            unknown_tables: self
+                .unknown_tables
+                .iter()
+                .map(RawTableRef::to_owned)
+                .collect(),
+        }
+    }
+}
+
+impl CharacterTableRef<'_> {
+    /// Copies every borrowed field, producing the equivalent owned [`CharacterTable`].
+    pub fn to_owned(&self) -> CharacterTable {
+        CharacterTable {
+            modifier_flags: self.modifier_flags,
+            configuration_flags: self.configuration_flags,
+            constant_code_point_count: self.constant_code_point_count,
+            link_flags: self.link_flags,
+            pixmap_table_indexes: self.pixmap_table_indexes.clone(),
+            characters: self
+                .characters
+                .iter()
+                .map(|character| Character {
+                    advance_x: character.advance_x,
+                    pixmap_index: character.pixmap_index,
+                    pixmap_table_index: character.pixmap_table_index,
+                    code_points: character.code_points.to_owned(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl PixmapTableRef<'_> {
+    /// Copies every borrowed field, producing the equivalent owned [`PixmapTable`].
+    pub fn to_owned(&self) -> PixmapTable {
+        PixmapTable {
+            configuration_flags: self.configuration_flags,
+            constant_width: self.constant_width,
+            constant_height: self.constant_height,
+            constant_bits_per_pixel: self.constant_bits_per_pixel,
+            link_flags: self.link_flags,
+            color_table_indexes: self.color_table_indexes.clone(),
+            pixmaps: self
+                .pixmaps
+                .iter()
+                .map(|pixmap| Pixmap {
+                    custom_width: pixmap.custom_width,
+                    custom_height: pixmap.custom_height,
+                    custom_bits_per_pixel: pixmap.custom_bits_per_pixel,
+                    data: pixmap.data.to_owned(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl FontTableRef<'_> {
+    /// Copies every borrowed field, producing the equivalent owned [`FontTable`].
+    pub fn to_owned(&self) -> FontTable {
+        FontTable {
+            link_flags: self.link_flags,
+            character_table_indexes: self.character_table_indexes.clone(),
+            fonts: self
+                .fonts
+                .iter()
+                .map(|font| Font {
+                    name: font.name.to_owned(),
+                    author: font.author.to_owned(),
+                    version: font.version,
+                    font_type: font.font_type,
+                    linked_character_table_indexes: font.linked_character_table_indexes.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl RawTableRef<'_> {
+    /// Copies the borrowed body, producing the equivalent owned [`RawTable`].
+    pub fn to_owned(&self) -> RawTable {
+        RawTable::new(self.identifier, self.data.to_owned())
+    }
+}
+
+/// Borrows the bytes between `start` and `end` of `buffer` as a string that the engine has
+/// already decoded character by character.
+fn borrow_str<'a, R: ByteReader, T: TagWriter>(
+    engine: &DeserializeEngine<R, T>,
+    buffer: &'a [u8],
+    start: usize,
+    end: usize,
+) -> Result<&'a str, DeserializeError> {
+    core::str::from_utf8(&buffer[start..end]).map_err(|_| DeserializeError::InvalidUtf8 {
+        table: engine.tagging_data.current_table_index,
+        record: engine.tagging_data.current_record_index,
+    })
+}
+
+fn next_character_table<'a, R: ByteReader, T: TagWriter>(
+    engine: &mut DeserializeEngine<R, T>,
+    buffer: &'a [u8],
+) -> Result<CharacterTableRef<'a>, DeserializeError> {
+    // Holds the table header, and is reused to decode each record before it is borrowed.
+    let mut header = CharacterTable::default();
+    header.next_modifer_flags(engine)?;
+    header.next_configurations(engine)?;
+    header.next_table_links(engine)?;
+
+    let character_count = engine.bytes.next()?;
+    let mut characters = Vec::with_capacity(character_count as usize);
+    let mut scratch = Character::default();
+    for index in 0..character_count {
+        engine.tagging_data.current_record_index = index;
+        engine.tagging_data.in_record = true;
+
+        header.next_character_modifiers(engine, &mut scratch)?;
+        scratch.code_points.clear();
+        let start = engine.bytes.index();
+        next_code_points(engine, &mut scratch, header.constant_code_point_count)?;
+        let mut end = engine.bytes.index();
+        if header.constant_code_point_count.is_none() {
+            // Skip the terminating null byte.
+            end -= 1;
+        }
+
+        characters.push(CharacterRef {
+            advance_x: scratch.advance_x,
+            pixmap_index: scratch.pixmap_index,
+            pixmap_table_index: scratch.pixmap_table_index,
+            code_points: borrow_str(engine, buffer, start, end)?,
+        });
+    }
+
+    Ok(CharacterTableRef {
+        modifier_flags: header.modifier_flags,
+        configuration_flags: header.configuration_flags,
+        constant_code_point_count: header.constant_code_point_count,
+        link_flags: header.link_flags,
+        pixmap_table_indexes: header.pixmap_table_indexes,
+        characters,
+    })
+}
+
+fn next_pixmap_table<'a, R: ByteReader, T: TagWriter>(
+    engine: &mut DeserializeEngine<R, T>,
+    buffer: &'a [u8],
+) -> Result<PixmapTableRef<'a>, DeserializeError> {
+    let mut header = PixmapTable::default();
+    header.next_modifier_flags(engine)?;
+    header.next_configurations(engine)?;
+    header.next_table_links(engine)?;
+
+    let pixmap_count = engine.bytes.next()?;
+    let mut pixmaps = Vec::with_capacity(pixmap_count as usize);
+    let mut scratch = Pixmap::default();
+    for index in 0..pixmap_count {
+        engine.tagging_data.current_record_index = index;
+        engine.tagging_data.in_record = true;
+
+        next_width(engine, &mut scratch, header.constant_width)?;
+        next_height(engine, &mut scratch, header.constant_height)?;
+        next_bits_per_pixel(engine, &mut scratch, header.constant_bits_per_pixel)?;
+        scratch.data.clear();
+        let start = engine.bytes.index();
+        next_pixmap(
+            engine,
+            &mut scratch,
+            header.constant_width,
+            header.constant_height,
+            header.constant_bits_per_pixel,
+        )?;
+
+        pixmaps.push(PixmapRef {
+            custom_width: scratch.custom_width,
+            custom_height: scratch.custom_height,
+            custom_bits_per_pixel: scratch.custom_bits_per_pixel,
+            data: &buffer[start..engine.bytes.index()],
+        });
+    }
+
+    Ok(PixmapTableRef {
+        configuration_flags: header.configuration_flags,
+        constant_width: header.constant_width,
+        constant_height: header.constant_height,
+        constant_bits_per_pixel: header.constant_bits_per_pixel,
+        link_flags: header.link_flags,
+        color_table_indexes: header.color_table_indexes,
+        pixmaps,
+    })
+}
+
+fn next_font_table<'a, R: ByteReader, T: TagWriter>(
+    engine: &mut DeserializeEngine<R, T>,
+    buffer: &'a [u8],
+) -> Result<FontTableRef<'a>, DeserializeError> {
+    let mut header = FontTable::default();
+    header.next_modifer_flags(engine)?;
+    header.next_configurations(engine)?;
+    header.next_table_links(engine)?;
+
+    let font_count = engine.bytes.next()?;
+    let mut fonts = Vec::with_capacity(font_count as usize);
+    let mut scratch = Font::default();
+    for index in 0..font_count {
+        engine.tagging_data.current_record_index = index;
+        engine.tagging_data.in_record = true;
+
+        // Both strings are followed by a terminating null byte.
+        let name_start = engine.bytes.index();
+        next_name(engine, &mut scratch)?;
+        let name = borrow_str(engine, buffer, name_start, engine.bytes.index() - 1)?;
+        let author_start = engine.bytes.index();
+        next_author(engine, &mut scratch)?;
+        let author = borrow_str(engine, buffer, author_start, engine.bytes.index() - 1)?;
+        next_version(engine, &mut scratch)?;
+        next_font_type(engine, &mut scratch)?;
+        next_linked_character_table_indexes(engine, &mut scratch)?;
+
+        fonts.push(FontRef {
+            name,
+            author,
+            version: scratch.version,
+            font_type: scratch.font_type,
+            linked_character_table_indexes: core::mem::take(
+                &mut scratch.linked_character_table_indexes,
+            ),
+        });
+    }
+
+    Ok(FontTableRef {
+        link_flags: header.link_flags,
+        character_table_indexes: header.character_table_indexes,
+        fonts,
+    })
+}
+
+fn next_raw_table<'a, R: ByteReader, T: TagWriter>(
+    engine: &mut DeserializeEngine<R, T>,
+    buffer: &'a [u8],
+    identifier: u8,
+) -> Result<RawTableRef<'a>, DeserializeError> {
+    let mut length = [0; 4];
+    for byte in length.iter_mut() {
+        *byte = engine.bytes.next()?;
+    }
+    let length = u32::from_le_bytes(length) as usize;
+
+    let start = engine.bytes.index();
+    let data = buffer
+        .get(start..start.saturating_add(length))
+        .ok_or(DeserializeError::UnexpectedEndOfFile)?;
+    for _ in 0..length {
+        engine.bytes.next()?;
+    }
+
+    Ok(RawTableRef { identifier, data })
+}
+
+fn deserialize_layout_ref<'a>(
+    engine: &mut DeserializeEngine<'a>,
+    buffer: &'a [u8],
+    layout: &mut LayoutRef<'a>,
+) -> Result<(), DeserializeError> {
+    deserialize::next_signature(engine)?;
+    deserialize::next_version(engine)?;
+    let header = engine.bytes.byte_index();
+    deserialize::next_header(engine)?;
+    layout.version = engine.layout.version;
+    if engine.layout.compact {
+        return Err(engine.error_at(header, DeserializeError::UnsupportedCompactLayout));
+    }
+
+    deserialize::next_tables(engine, |engine, entry| {
+        let Some(table_type) = entry.table_type else {
+            let table = next_raw_table(engine, buffer, entry.identifier)?;
+            layout.unknown_tables.push(table);
+            return Ok(());
+        };
+        match table_type {
+            TableType::Character => {
+                let table = next_character_table(engine, buffer)?;
+                layout.character_tables.push(table);
+            }
+            TableType::Pixmap => {
+                let table = next_pixmap_table(engine, buffer)?;
+                layout.pixmap_tables.push(table);
+            }
+            TableType::Color => {
+                let mut table = ColorTable::default();
+                table.deserialize(engine)?;
+                layout.color_tables.push(table);
+            }
+            TableType::Font => {
+                let table = next_font_table(engine, buffer)?;
+                layout.font_tables.push(table);
+            }
+        }
+        Ok(())
+    })
+}
+
+/// Parses a [`&[u8]`] into a [`LayoutRef`] that borrows from `buffer` rather than copying
+/// variable length data. Fails with [`DeserializeError::UnsupportedCompactLayout`] if the
+/// file is compact, as its records are then not byte aligned.
+pub fn layout_ref_from_data(buffer: &[u8]) -> Result<LayoutRef<'_>, DeserializeErrorContext> {
+    layout_ref_from_data_with_options(buffer, DeserializeOptions::default())
+}
+
+/// Parses a [`&[u8]`] into a [`LayoutRef`] like [`layout_ref_from_data`], using the given
+/// [`DeserializeOptions`].
+pub fn layout_ref_from_data_with_options(
+    buffer: &[u8],
+    options: DeserializeOptions,
+) -> Result<LayoutRef<'_>, DeserializeErrorContext> {
+    let mut engine = DeserializeEngine::from_data(buffer);
+    engine.options = options;
+    let mut layout = LayoutRef {
+        version: Version::default(),
+        character_tables: Vec::new(),
+        color_tables: Vec::new(),
+        pixmap_tables: Vec::new(),
+        font_tables: Vec::new(),
+        unknown_tables: Vec::new(),
+    };
+    deserialize_layout_ref(&mut engine, buffer, &mut layout)
+        .map_err(|error| engine.error_context(error))?;
+    Ok(layout)
+}
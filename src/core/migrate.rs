@@ -0,0 +1,75 @@
+/*
+ * Copyright 2025 SimplePixelFont
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::core::*;
+use crate::Vec;
+
+/// Returned by [`Layout::migrate_to`] when the layout uses features the target [`Version`]
+/// cannot express.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MigrationError {
+    /// The version the layout was being migrated to.
+    pub target: Version,
+    /// Every feature in use that `target` does not support.
+    pub unsupported: Vec<VersionFeature>,
+}
+
+impl core::fmt::Display for MigrationError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "cannot migrate to {}, unsupported: ", self.target)?;
+        for (index, feature) in self.unsupported.iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", feature)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MigrationError {}
+
+impl Layout {
+    /// Returns every [`VersionFeature`] this layout makes use of.
+    pub fn features(&self) -> Vec<VersionFeature> {
+        let mut features = Vec::new();
+        if !self.unknown_tables.is_empty() {
+            features.push(VersionFeature::ExtensionTables);
+        }
+        features
+    }
+
+    /// Changes the [`Version`] this layout will be serialized as. Upgrading always succeeds;
+    /// downgrading fails, leaving the layout untouched, if any feature in use cannot be
+    /// expressed in `version`.
+    pub fn migrate_to(&mut self, version: Version) -> Result<(), MigrationError> {
+        let unsupported: Vec<VersionFeature> = self
+            .features()
+            .into_iter()
+            .filter(|feature| !version.supports(*feature))
+            .collect();
+        if !unsupported.is_empty() {
+            return Err(MigrationError {
+                target: version,
+                unsupported,
+            });
+        }
+
+        self.version = version;
+        Ok(())
+    }
+}
@@ -20,10 +20,22 @@
 //! binary file. Additionally it defines the [`layout_to_data`] and [`layout_from_data`] functions that
 //! can be used to convert between the structs and the binary data.
 
+pub(crate) mod builder;
 pub mod byte;
+pub(crate) mod character_index;
 pub(crate) mod deserialize;
+pub(crate) mod directory;
+pub(crate) mod layout_ref;
+pub(crate) mod migrate;
+pub(crate) mod normalize;
+pub(crate) mod optimize;
+pub(crate) mod resolve;
+pub(crate) mod semantic;
 pub(crate) mod serialize;
+pub(crate) mod shard;
 pub(crate) mod tables;
+pub(crate) mod validate;
+pub(crate) mod verify;
 
 use bitflags::bitflags;
 use byte::{ByteReader, ByteReaderImpl};
@@ -36,7 +48,22 @@ pub(crate) use crate::tagging::*;
 #[cfg(not(feature = "tagging"))]
 pub(crate) use tagging_stub::*;
 
-use crate::{String, Vec};
+pub use builder::{
+    CharacterTableBuilder, CharacterTableHandle, ColorTableBuilder, ColorTableHandle, FontBuilder,
+    LayoutBuilder, PixmapHandle, PixmapTableBuilder, PixmapTableHandle,
+};
+pub use character_index::{CharacterIndex, GlyphRef, ResolvedCharacter};
+pub use directory::{TableDirectory, TableEntry};
+pub use layout_ref::{
+    layout_ref_from_data, layout_ref_from_data_with_options, CharacterRef, CharacterTableRef,
+    FontRef, FontTableRef, LayoutRef, PixmapRef, PixmapTableRef, RawTableRef,
+};
+pub use migrate::MigrationError;
+pub use resolve::{Palette, ResolveError, ResolvedColor, ResolvedFont, ResolvedGlyph};
+pub use tables::pixmap::{Pixels, Rows};
+pub use validate::{Diagnostic, Severity};
+
+use crate::{vec, String, Vec};
 use core::marker::PhantomData;
 
 bitflags! {
@@ -138,13 +165,54 @@ bitflags! {
 
 #[repr(u8)]
 #[non_exhaustive]
-#[derive(Default, Debug, Clone, Copy)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[doc = include_str!("../../res/snippets/data_types/Version.md")]
 pub enum Version {
     #[default]
     #[doc = include_str!("../../res/snippets/data_types/Version/FV0.md")]
     FV0 = 0b00000000,
+    #[doc = include_str!("../../res/snippets/data_types/Version/FV1.md")]
+    FV1 = 0b00000001,
+}
+
+impl Version {
+    /// The newest format version this crate can read and write.
+    pub const LATEST: Version = Version::FV1;
+
+    /// Returns `true` if files of this version can express `feature`.
+    pub fn supports(self, feature: VersionFeature) -> bool {
+        self >= feature.introduced_in()
+    }
+}
+
+/// A part of the format that is only available from a certain [`Version`] onward.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionFeature {
+    /// Length-prefixed extension tables, stored in [`Layout::unknown_tables`].
+    ExtensionTables,
+    /// Reserved bits of every flags byte must be zero, so later versions can give them a
+    /// meaning. Readers reject files that set them, as in strict mode, and writers clear them.
+    ZeroReservedBits,
+}
+
+impl VersionFeature {
+    /// The first [`Version`] able to express this feature.
+    pub fn introduced_in(self) -> Version {
+        match self {
+            VersionFeature::ExtensionTables | VersionFeature::ZeroReservedBits => Version::FV1,
+        }
+    }
+}
+
+impl core::fmt::Display for VersionFeature {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            VersionFeature::ExtensionTables => write!(f, "extension tables"),
+            VersionFeature::ZeroReservedBits => write!(f, "zeroed reserved bits"),
+        }
+    }
 }
 
 impl core::fmt::Display for Version {
@@ -155,7 +223,7 @@ impl core::fmt::Display for Version {
 }
 
 #[non_exhaustive]
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// The full, decoded contents of a `.spf` file: its format version, packing mode, and every table it defines.
 pub struct Layout {
@@ -173,10 +241,36 @@ pub struct Layout {
     pub pixmap_tables: Vec<PixmapTable>,
     /// The font tables defined in this file.
     pub font_tables: Vec<FontTable>,
+    /// Extension tables this version of the crate does not understand, kept verbatim so they
+    /// survive a round trip. They are written after every known table.
+    pub unknown_tables: Vec<RawTable>,
 }
 
 #[non_exhaustive]
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// An extension table kept as raw bytes, available from [`Version::FV1`]. Extension tables use identifiers from
+/// [`RawTable::MIN_IDENTIFIER`] upward, followed by the length of their body as a little
+/// endian `u32` and the body itself, so readers can skip tables they do not recognize.
+pub struct RawTable {
+    /// The table identifier, at least [`RawTable::MIN_IDENTIFIER`].
+    pub identifier: u8,
+    /// The table body, excluding the identifier and length prefix.
+    pub data: Vec<u8>,
+}
+
+impl RawTable {
+    /// The lowest identifier reserved for length-prefixed extension tables.
+    pub const MIN_IDENTIFIER: u8 = 0b10000000;
+
+    /// Creates a [`RawTable`] from its identifier and body.
+    pub fn new(identifier: u8, data: Vec<u8>) -> Self {
+        Self { identifier, data }
+    }
+}
+
+#[non_exhaustive]
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[doc = include_str!("../../res/snippets/pixmap_table/brief.md")]
 pub struct PixmapTable {
@@ -203,7 +297,7 @@ pub struct PixmapTable {
 }
 
 #[non_exhaustive]
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// A single glyph's pixel data within a [`PixmapTable`].
 pub struct Pixmap {
@@ -222,7 +316,7 @@ pub struct Pixmap {
 }
 
 #[non_exhaustive]
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[doc = include_str!("../../res/snippets/character_table/brief.md")]
 pub struct CharacterTable {
@@ -246,7 +340,7 @@ pub struct CharacterTable {
 }
 
 #[non_exhaustive]
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// A single character's mapping to a pixmap within a [`CharacterTable`].
 pub struct Character {
@@ -267,7 +361,7 @@ pub struct Character {
 }
 
 #[non_exhaustive]
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[doc = include_str!("../../res/snippets/color_table/brief.md")]
 pub struct ColorTable {
@@ -286,7 +380,7 @@ pub struct ColorTable {
 
 #[repr(u8)]
 #[non_exhaustive]
-#[derive(Default, Debug, Clone, Copy)]
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[doc = include_str!("../../res/snippets/data_types/ColorType.md")]
 pub enum ColorType {
@@ -298,7 +392,7 @@ pub enum ColorType {
 }
 
 #[non_exhaustive]
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// A single RGBA color value within a [`ColorTable`].
 pub struct Color {
@@ -320,7 +414,7 @@ pub struct Color {
 }
 
 #[non_exhaustive]
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[doc = include_str!("../../res/snippets/font_table/brief.md")]
 pub struct FontTable {
@@ -335,7 +429,7 @@ pub struct FontTable {
 }
 
 #[non_exhaustive]
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 /// A single named font within a [`FontTable`], grouping the [`CharacterTable`]s it uses.
 pub struct Font {
@@ -356,6 +450,74 @@ pub struct Font {
     pub linked_character_table_indexes: Vec<u8>,
 }
 
+/// A bit-precision position within a `.spf` file's byte stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteIndex {
+    /// The byte offset from the start of the file.
+    pub byte: usize,
+    /// The bit offset within `byte`, `0`-`7`.
+    pub bit: u8,
+}
+
+impl ByteIndex {
+    /// Constructs a [`ByteIndex`] at an explicit byte and bit offset.
+    pub fn new(byte: usize, bit: u8) -> Self {
+        Self { byte, bit }
+    }
+
+    /// Constructs a [`ByteIndex`] at the start of `byte` (bit `0`).
+    pub fn at_byte(byte: usize) -> Self {
+        Self { byte, bit: 0 }
+    }
+}
+
+impl core::fmt::Display for ByteIndex {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "byte {:#x}", self.byte)?;
+        if self.bit != 0 {
+            write!(f, " bit {}", self.bit)?;
+        }
+        Ok(())
+    }
+}
+
+/// Identifies which of the four table kinds a table, tag, or diagnostic refers to.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TableType {
+    /// A [`CharacterTable`].
+    Character,
+    /// A [`PixmapTable`].
+    Pixmap,
+    /// A [`ColorTable`].
+    Color,
+    /// A [`FontTable`].
+    Font,
+}
+
+impl core::fmt::Display for TableType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            TableType::Character => write!(f, "character table"),
+            TableType::Pixmap => write!(f, "pixmap table"),
+            TableType::Color => write!(f, "color table"),
+            TableType::Font => write!(f, "font table"),
+        }
+    }
+}
+
+impl TableType {
+    /// The name of a single record in this kind of table.
+    pub(crate) fn record_name(&self) -> &'static str {
+        match self {
+            TableType::Character => "character",
+            TableType::Pixmap => "pixmap",
+            TableType::Color => "color",
+            TableType::Font => "font",
+        }
+    }
+}
+
 #[repr(u8)]
 #[non_exhaustive]
 #[rustfmt::skip]
@@ -375,7 +537,7 @@ impl TryFrom<u8> for TableIdentifier {
             0b00000010 => Ok(TableIdentifier::Pixmap),
             0b00000011 => Ok(TableIdentifier::Color),
             0b00000100 => Ok(TableIdentifier::Font),
-            _ => Err(DeserializeError::UnsupportedTableIdentifier),
+            _ => Err(DeserializeError::UnsupportedTableIdentifier { value }),
         }
     }
 }
@@ -386,7 +548,8 @@ impl TryFrom<u8> for Version {
     fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
             0b00000000 => Ok(Version::FV0),
-            _ => Err(DeserializeError::UnsupportedVersion),
+            0b00000001 => Ok(Version::FV1),
+            _ => Err(DeserializeError::UnsupportedVersion { value }),
         }
     }
 }
@@ -398,7 +561,7 @@ impl TryFrom<u8> for ColorType {
         match value {
             0 => Ok(ColorType::Dynamic),
             1 => Ok(ColorType::Absolute),
-            _ => Err(DeserializeError::UnsupportedColorType),
+            _ => Err(DeserializeError::UnsupportedColorType { value }),
         }
     }
 }
@@ -407,7 +570,7 @@ impl TryFrom<u8> for FontType {
     type Error = DeserializeError;
 
     fn try_from(value: u8) -> Result<Self, Self::Error> {
-        FontType::from_bits(value).ok_or(DeserializeError::UnsupportedFontType)
+        FontType::from_bits(value).ok_or(DeserializeError::UnsupportedFontType { value })
     }
 }
 
@@ -420,13 +583,131 @@ pub enum DeserializeError {
     #[doc = include_str!("../../res/snippets/errors/invalid_signature.md")]
     InvalidSignature,
     #[doc = include_str!("../../res/snippets/errors/unsupported_version.md")]
-    UnsupportedVersion,
+    UnsupportedVersion {
+        /// The version byte that was read.
+        value: u8,
+    },
     #[doc = include_str!("../../res/snippets/errors/unsupported_color_type.md")]
-    UnsupportedColorType,
+    UnsupportedColorType {
+        /// The color type byte that was read.
+        value: u8,
+    },
     #[doc = include_str!("../../res/snippets/errors/unsupported_table_identifier.md")]
-    UnsupportedTableIdentifier,
+    UnsupportedTableIdentifier {
+        /// The table identifier byte that was read.
+        value: u8,
+    },
     #[doc = include_str!("../../res/snippets/errors/unsupported_font_type.md")]
-    UnsupportedFontType,
+    UnsupportedFontType {
+        /// The font type byte that was read.
+        value: u8,
+    },
+    #[doc = include_str!("../../res/snippets/errors/invalid_utf8.md")]
+    InvalidUtf8 {
+        /// Index of the [`CharacterTable`] or [`FontTable`] containing the malformed string.
+        table: u8,
+        /// Index of the record within that table.
+        record: u8,
+    },
+    #[doc = include_str!("../../res/snippets/errors/trailing_data.md")]
+    TrailingData,
+    #[doc = include_str!("../../res/snippets/errors/reserved_bits_set.md")]
+    ReservedBitsSet {
+        /// The flags byte that was read.
+        value: u8,
+    },
+    #[doc = include_str!("../../res/snippets/errors/limit_exceeded.md")]
+    LimitExceeded {
+        /// The limit that was exceeded.
+        which: Limit,
+    },
+    #[doc = include_str!("../../res/snippets/errors/unsupported_compact_layout.md")]
+    UnsupportedCompactLayout,
+    #[cfg(feature = "std")]
+    #[doc = include_str!("../../res/snippets/errors/io.md")]
+    Io(std::io::Error),
+}
+
+impl core::fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            DeserializeError::UnexpectedEndOfFile => write!(f, "unexpected end of file"),
+            DeserializeError::InvalidSignature => write!(f, "invalid signature"),
+            DeserializeError::UnsupportedVersion { value } => {
+                write!(f, "unsupported version {}", value)
+            }
+            DeserializeError::UnsupportedColorType { value } => {
+                write!(f, "unsupported color type {}", value)
+            }
+            DeserializeError::UnsupportedTableIdentifier { value } => {
+                write!(f, "unsupported table identifier {}", value)
+            }
+            DeserializeError::UnsupportedFontType { value } => {
+                write!(f, "unsupported font type {}", value)
+            }
+            DeserializeError::InvalidUtf8 { table, record } => {
+                write!(f, "invalid UTF-8 in table {}, record {}", table, record)
+            }
+            DeserializeError::TrailingData => write!(f, "trailing data after the last table"),
+            DeserializeError::ReservedBitsSet { value } => {
+                write!(f, "reserved bits set in flags {:#010b}", value)
+            }
+            DeserializeError::LimitExceeded { which } => write!(f, "{} limit exceeded", which),
+            DeserializeError::UnsupportedCompactLayout => {
+                write!(f, "compact layouts cannot be borrowed from")
+            }
+            #[cfg(feature = "std")]
+            DeserializeError::Io(error) => write!(f, "I/O error: {}", error),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DeserializeError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            DeserializeError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// A [`DeserializeError`] together with where in the input it occurred.
+#[non_exhaustive]
+#[derive(Debug)]
+pub struct DeserializeErrorContext {
+    /// What went wrong.
+    pub error: DeserializeError,
+    /// Position where the field that failed to decode starts, or where reading stopped for
+    /// [`DeserializeError::UnexpectedEndOfFile`], [`DeserializeError::TrailingData`] and I/O
+    /// errors.
+    pub byte_index: ByteIndex,
+    /// The kind of table being read, or `None` if the error occurred outside of a table.
+    pub table_type: Option<TableType>,
+    /// Index of that table within its [`Layout`] vector.
+    pub table_index: Option<u8>,
+    /// Index of the record being read within that table, or `None` if the error occurred
+    /// in the table's header.
+    pub record_index: Option<u8>,
+}
+
+impl core::fmt::Display for DeserializeErrorContext {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if let (Some(table_type), Some(table_index)) = (self.table_type, self.table_index) {
+            write!(f, "{} {}, ", table_type, table_index)?;
+            if let Some(record_index) = self.record_index {
+                write!(f, "{} {}, ", table_type.record_name(), record_index)?;
+            }
+        }
+        write!(f, "{}: {}", self.byte_index, self.error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for DeserializeErrorContext {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
 }
 
 #[non_exhaustive]
@@ -437,28 +718,140 @@ pub enum SerializeError {
     StaticVectorTooLarge,
     #[doc = include_str!("../../res/snippets/errors/invalid_pixmap_data.md")]
     InvalidPixmapData,
+    #[doc = include_str!("../../res/snippets/errors/missing_field.md")]
+    MissingField {
+        /// The kind of table the field belongs to.
+        table_type: TableType,
+        /// Index of that table within its [`Layout`] vector.
+        table_index: usize,
+        /// Index of the record within that table, or `None` for a table-level field.
+        record_index: Option<usize>,
+        /// Name of the missing field.
+        field: &'static str,
+    },
+    #[doc = include_str!("../../res/snippets/errors/unflagged_field.md")]
+    UnflaggedField {
+        /// The kind of table the field belongs to.
+        table_type: TableType,
+        /// Index of that table within its [`Layout`] vector.
+        table_index: usize,
+        /// Index of the record within that table, or `None` for a table-level field.
+        record_index: Option<usize>,
+        /// Name of the field whose flag is not set.
+        field: &'static str,
+    },
+    #[doc = include_str!("../../res/snippets/errors/unsupported_table_identifier.md")]
+    UnsupportedTableIdentifier {
+        /// The identifier of the [`RawTable`] being written.
+        value: u8,
+    },
+    #[doc = include_str!("../../res/snippets/errors/unsupported_feature.md")]
+    UnsupportedFeature {
+        /// The version the layout is being written as.
+        version: Version,
+        /// The feature that version cannot express.
+        feature: VersionFeature,
+    },
+    #[doc = include_str!("../../res/snippets/errors/buffer_full.md")]
+    BufferFull,
+    #[doc = include_str!("../../res/snippets/errors/round_trip_mismatch.md")]
+    RoundTripMismatch {
+        /// Path of the first differing field, such as `pixmap_tables[0].pixmaps[1].data`.
+        field: String,
+    },
+    #[doc = include_str!("../../res/snippets/errors/round_trip_unreadable.md")]
+    RoundTripUnreadable(DeserializeErrorContext),
+    #[doc = include_str!("../../res/snippets/errors/unresolved_character.md")]
+    UnresolvedCharacter {
+        /// Index of the character table within [`Layout::character_tables`].
+        table_index: usize,
+        /// Index of the character within that table.
+        record_index: usize,
+    },
+    #[cfg(feature = "std")]
+    #[doc = include_str!("../../res/snippets/errors/io.md")]
+    Io(std::io::Error),
 }
 
 pub(crate) trait Table: Sized {
+    /// Reads the table following its identifier into `self`. Records are pushed once fully
+    /// read, so on error `self` keeps every record decoded before the failing one.
     fn deserialize<R: ByteReader, T: TagWriter>(
+        &mut self,
         engine: &mut DeserializeEngine<R, T>,
-    ) -> Result<Self, DeserializeError>;
+    ) -> Result<(), DeserializeError>;
     fn serialize<T: TagWriter>(
         &self,
         engine: &mut SerializeEngine<T>,
     ) -> Result<(), SerializeError>;
 }
 
+/// Options controlling how a [`DeserializeEngine`] parses its input.
+#[non_exhaustive]
+#[derive(Default, Debug, Clone, Copy)]
+pub struct DeserializeOptions {
+    /// Reject input that the lenient parser would silently accept: non-zero padding bits
+    /// after the last table, and reserved bits set in any flags byte (including the link
+    /// flags of a [`ColorTable`], which has no links). A byte after the last table is read
+    /// as another table in either mode.
+    pub strict: bool,
+    /// Caps on the work done while parsing, for untrusted input.
+    pub limits: Limits,
+}
+
+/// Upper bounds checked by a [`DeserializeEngine`] while parsing. `None` means unlimited.
+/// Exceeding a limit fails with [`DeserializeError::LimitExceeded`].
+#[non_exhaustive]
+#[derive(Default, Debug, Clone, Copy)]
+pub struct Limits {
+    /// Maximum number of tables, of all types combined.
+    pub max_tables: Option<usize>,
+    /// Maximum number of pixmap data bytes, summed over every [`Pixmap`].
+    pub max_pixmap_bytes: Option<usize>,
+    /// Maximum number of code points in a single [`Character`].
+    pub max_code_points: Option<usize>,
+    /// Maximum length in bytes of [`Font::name`] and [`Font::author`].
+    pub max_string_length: Option<usize>,
+}
+
+/// Identifies which entry of [`Limits`] was exceeded.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    /// [`Limits::max_tables`]
+    Tables,
+    /// [`Limits::max_pixmap_bytes`]
+    PixmapBytes,
+    /// [`Limits::max_code_points`]
+    CodePoints,
+    /// [`Limits::max_string_length`]
+    StringLength,
+}
+
+impl core::fmt::Display for Limit {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Limit::Tables => write!(f, "table count"),
+            Limit::PixmapBytes => write!(f, "pixmap data size"),
+            Limit::CodePoints => write!(f, "code point count"),
+            Limit::StringLength => write!(f, "string length"),
+        }
+    }
+}
+
 /// Drives parsing of a `.spf` byte source into a [`Layout`].
 pub struct DeserializeEngine<'a, R: ByteReader = ByteReaderImpl<'a>, T: TagWriter = TagWriterNoOp> {
     bytes: R,
     /// The resulting [`Layout`] after reading from `bytes`.
     pub layout: Layout,
+    /// Options controlling how strictly `bytes` is parsed.
+    pub options: DeserializeOptions,
     #[cfg(feature = "tagging")]
     /// Collection of tags marking the byte/bit span of every field read, when the `tagging` feature is enabled.
     pub tags: T,
-    #[cfg(feature = "tagging")]
     tagging_data: TaggingData,
+    pixmap_bytes: usize,
+    error_start: Option<ByteIndex>,
     _phantom: PhantomData<T>,
     _phantom2: &'a PhantomData<R>,
 }
@@ -466,19 +859,40 @@ pub struct DeserializeEngine<'a, R: ByteReader = ByteReaderImpl<'a>, T: TagWrite
 #[non_exhaustive]
 #[derive(Default)]
 pub(crate) struct TaggingData {
+    current_table_type: Option<TableType>,
     current_table_index: u8,
     current_record_index: u8,
+    in_record: bool,
+}
+
+impl TaggingData {
+    fn begin_table(&mut self, table_type: TableType, index: usize) {
+        self.current_table_type = Some(table_type);
+        self.current_table_index = index as u8;
+        self.in_record = false;
+    }
+}
+
+/// Options controlling how [`layout_to_data_with_options`] serializes a [`Layout`].
+#[non_exhaustive]
+#[derive(Default, Debug, Clone, Copy)]
+pub struct SerializeOptions {
+    /// Serialize a copy of the layout with every flag recomputed by [`Layout::normalize`], so
+    /// flags that disagree with their `Option` fields cannot produce a corrupt file.
+    pub normalize: bool,
+    /// Serialize a copy of the layout with every table past the 255 record limit split by
+    /// [`Layout::shard`], before any normalization.
+    pub shard: bool,
 }
 
 /// Drives serialization of a [`Layout`] into `.spf` bytes.
 pub struct SerializeEngine<'a, T: TagWriter = TagWriterNoOp> {
-    bytes: byte::ByteWriter,
+    bytes: byte::ByteWriter<'a>,
     /// The [`Layout`] being serialized into `bytes`.
     pub layout: &'a Layout,
     #[cfg(feature = "tagging")]
     /// Collection of tags marking the byte/bit span of every field written, when the `tagging` feature is enabled.
     pub tags: T,
-    #[cfg(feature = "tagging")]
     tagging_data: TaggingData,
     _phantom: PhantomData<T>,
 }
@@ -490,60 +904,94 @@ pub(crate) fn deserialize_layout<R: ByteReader, T: TagWriter>(
     deserialize::next_version(engine)?;
     deserialize::next_header(engine)?;
 
-    while engine.bytes.index() < engine.bytes.len() - 1 {
-        match engine.bytes.next().try_into()? {
-            TableIdentifier::Character => {
-                #[cfg(feature = "tagging")]
-                {
-                    engine.tagging_data.current_table_index =
-                        engine.layout.character_tables.len() as u8;
-                }
-                let table = CharacterTable::deserialize(engine)?;
+    deserialize::next_tables(engine, |engine, entry| {
+        let Some(table_type) = entry.table_type else {
+            let table = deserialize::next_raw_table(engine, entry.identifier)?;
+            engine.layout.unknown_tables.push(table);
+            return Ok(());
+        };
+        match table_type {
+            TableType::Character => {
+                let mut table = CharacterTable::default();
+                let result = table.deserialize(engine);
                 engine.layout.character_tables.push(table);
+                result
             }
-            TableIdentifier::Pixmap => {
-                #[cfg(feature = "tagging")]
-                {
-                    engine.tagging_data.current_table_index =
-                        engine.layout.pixmap_tables.len() as u8;
-                }
-                let table = PixmapTable::deserialize(engine)?;
+            TableType::Pixmap => {
+                let mut table = PixmapTable::default();
+                let result = table.deserialize(engine);
                 engine.layout.pixmap_tables.push(table);
+                result
             }
-            TableIdentifier::Color => {
-                #[cfg(feature = "tagging")]
-                {
-                    engine.tagging_data.current_table_index =
-                        engine.layout.color_tables.len() as u8;
-                }
-                let table = ColorTable::deserialize(engine)?;
+            TableType::Color => {
+                let mut table = ColorTable::default();
+                let result = table.deserialize(engine);
                 engine.layout.color_tables.push(table);
+                result
             }
-            TableIdentifier::Font => {
-                #[cfg(feature = "tagging")]
-                {
-                    engine.tagging_data.current_table_index = engine.layout.font_tables.len() as u8;
-                }
-                let table = FontTable::deserialize(engine)?;
+            TableType::Font => {
+                let mut table = FontTable::default();
+                let result = table.deserialize(engine);
                 engine.layout.font_tables.push(table);
+                result
             }
-        };
-    }
-    Ok(())
+        }
+    })
 }
 
 /// Deserializes into `engine`'s [`Layout`] using an already-constructed [`DeserializeEngine`]. Use [`layout_from_data`] unless you need direct control over the engine (for example, a custom [`ByteReader`] or [`TagWriter`]).
+///
+/// On error, `engine.layout` keeps every table decoded so far, including the failing table
+/// with the records read before the error.
 pub fn deserialize_with_engine<R: ByteReader, T: TagWriter>(
     engine: &mut DeserializeEngine<R, T>,
-) -> Result<(), DeserializeError> {
-    deserialize_layout(engine)?;
+) -> Result<(), DeserializeErrorContext> {
+    deserialize_layout(engine).map_err(|error| engine.error_context(error))?;
     Ok(())
 }
 
 /// Parses a [`&[u8]`] into a font [`Layout`]. This function internally creates a [`DeserializeEngine`]
 /// and calls [`deserialize_with_engine`].
-pub fn layout_from_data(buffer: &[u8]) -> Result<Layout, DeserializeError> {
+pub fn layout_from_data(buffer: &[u8]) -> Result<Layout, DeserializeErrorContext> {
+    let mut engine = DeserializeEngine::from_data(buffer);
+    deserialize_with_engine(&mut engine)?;
+    Ok(engine.layout)
+}
+
+/// Parses a font [`Layout`] from any [`std::io::Read`], such as a file or socket, without
+/// knowing its length up front. The reader is buffered internally. I/O errors are reported
+/// as [`DeserializeError::Io`].
+#[cfg(feature = "std")]
+pub fn layout_from_reader<R: std::io::Read>(reader: R) -> Result<Layout, DeserializeErrorContext> {
+    let reader = byte::ByteReaderRead::new(std::io::BufReader::new(reader));
+    let mut engine = DeserializeEngine::from_reader(reader);
+    deserialize_with_engine(&mut engine)?;
+    Ok(engine.layout)
+}
+
+/// Parses as much of a [`&[u8]`] as possible into a font [`Layout`]. Rather than failing,
+/// this function stops at the first error and returns everything decoded before it (see
+/// [`deserialize_with_engine`]) together with a [`Diagnostic`] describing the error.
+///
+/// The format has no table lengths to resynchronize on, so nothing after the error is
+/// recovered and at most one diagnostic is returned.
+pub fn layout_from_data_lossy(buffer: &[u8]) -> (Layout, Vec<Diagnostic>) {
     let mut engine = DeserializeEngine::from_data(buffer);
+    let diagnostics = match deserialize_with_engine(&mut engine) {
+        Ok(()) => Vec::new(),
+        Err(context) => vec![Diagnostic::from(context)],
+    };
+    (engine.layout, diagnostics)
+}
+
+/// Parses a [`&[u8]`] into a font [`Layout`] like [`layout_from_data`], using the given
+/// [`DeserializeOptions`].
+pub fn layout_from_data_with_options(
+    buffer: &[u8],
+    options: DeserializeOptions,
+) -> Result<Layout, DeserializeErrorContext> {
+    let mut engine = DeserializeEngine::from_data(buffer);
+    engine.options = options;
     deserialize_with_engine(&mut engine)?;
     Ok(engine.layout)
 }
@@ -556,33 +1004,32 @@ pub(crate) fn serialize_layout<T: TagWriter>(
     serialize::push_header(engine);
 
     for (index, character_table) in engine.layout.character_tables.iter().enumerate() {
-        #[cfg(feature = "tagging")]
-        {
-            engine.tagging_data.current_table_index = index as u8;
-        }
+        engine.tagging_data.current_table_index = index as u8;
         character_table.serialize(engine)?;
     }
     for (index, pixmap_table) in engine.layout.pixmap_tables.iter().enumerate() {
-        #[cfg(feature = "tagging")]
-        {
-            engine.tagging_data.current_table_index = index as u8;
-        }
+        engine.tagging_data.current_table_index = index as u8;
         pixmap_table.serialize(engine)?;
     }
     for (index, color_table) in engine.layout.color_tables.iter().enumerate() {
-        #[cfg(feature = "tagging")]
-        {
-            engine.tagging_data.current_table_index = index as u8;
-        }
+        engine.tagging_data.current_table_index = index as u8;
         color_table.serialize(engine)?;
     }
     for (index, font_table) in engine.layout.font_tables.iter().enumerate() {
-        #[cfg(feature = "tagging")]
-        {
-            engine.tagging_data.current_table_index = index as u8;
-        }
+        engine.tagging_data.current_table_index = index as u8;
         font_table.serialize(engine)?;
     }
+    if !engine.layout.unknown_tables.is_empty() && !engine.supports(VersionFeature::ExtensionTables)
+    {
+        return Err(SerializeError::UnsupportedFeature {
+            version: engine.layout.version,
+            feature: VersionFeature::ExtensionTables,
+        });
+    }
+    for (index, raw_table) in engine.layout.unknown_tables.iter().enumerate() {
+        engine.tagging_data.current_table_index = index as u8;
+        serialize::push_raw_table(engine, raw_table)?;
+    }
 
     Ok(())
 }
@@ -592,9 +1039,24 @@ pub fn serialize_with_engine<T: TagWriter>(
     engine: &mut SerializeEngine<T>,
 ) -> Result<(), SerializeError> {
     serialize_layout(engine)?;
+    engine.bytes.finish()?;
     Ok(())
 }
 
+/// Encodes the provided font [`Layout`] straight into a [`std::io::Write`], such as a file or
+/// socket, streaming bytes out as tables are encoded. Write errors are reported as
+/// [`SerializeError::Io`].
+#[cfg(feature = "std")]
+pub fn layout_to_writer<W: std::io::Write>(
+    layout: &Layout,
+    writer: W,
+) -> Result<(), SerializeError> {
+    let mut sink = byte::WriteSink(writer);
+    let mut engine = SerializeEngine::from_layout(layout);
+    engine.set_sink(&mut sink);
+    serialize_with_engine(&mut engine)
+}
+
 /// Encodes the provided font [`Layout`] into a [`Vec<u8>`]. This function internally creates a
 /// [`SerializeEngine`] and calls [`serialize_with_engine`].
 pub fn layout_to_data(layout: &Layout) -> Result<Vec<u8>, SerializeError> {
@@ -602,3 +1064,36 @@ pub fn layout_to_data(layout: &Layout) -> Result<Vec<u8>, SerializeError> {
     serialize_with_engine(&mut engine)?;
     Ok(engine.data_owned())
 }
+
+/// Encodes the provided font [`Layout`] into a [`Vec<u8>`] like [`layout_to_data`], applying
+/// the given [`SerializeOptions`] first.
+pub fn layout_to_data_with_options(
+    layout: &Layout,
+    options: SerializeOptions,
+) -> Result<Vec<u8>, SerializeError> {
+    if !options.normalize && !options.shard {
+        return layout_to_data(layout);
+    }
+
+    let mut layout = layout.clone();
+    if options.shard {
+        layout.shard()?;
+    }
+    if options.normalize {
+        layout.normalize();
+    }
+    layout_to_data(&layout)
+}
+
+/// Encodes the provided font [`Layout`] into a [`Vec<u8>`] like [`layout_to_data`], then parses
+/// the output again with [`layout_from_data`] and checks that it decodes to the same layout.
+/// Fields that do not survive a round trip, such as record fields whose modifier flag is not
+/// set, are reported as [`SerializeError::RoundTripMismatch`] naming the first differing field.
+pub fn layout_to_data_verified(layout: &Layout) -> Result<Vec<u8>, SerializeError> {
+    let data = layout_to_data(layout)?;
+    let decoded = layout_from_data(&data).map_err(SerializeError::RoundTripUnreadable)?;
+    if let Some(field) = verify::first_difference(layout, &decoded) {
+        return Err(SerializeError::RoundTripMismatch { field });
+    }
+    Ok(data)
+}
@@ -0,0 +1,129 @@
+/*
+ * Copyright 2025 SimplePixelFont
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::core::*;
+
+/// Returns `true` if `has_field` holds for any record in `records`.
+fn any_populated<T>(records: &[T], has_field: impl Fn(&T) -> bool) -> bool {
+    records.iter().any(has_field)
+}
+
+impl CharacterTable {
+    pub(crate) fn normalize(&mut self) {
+        let mut modifier_flags = CharacterTableModifierFlags::empty();
+        modifier_flags.set(
+            CharacterTableModifierFlags::UseAdvanceX,
+            any_populated(&self.characters, |character| character.advance_x.is_some()),
+        );
+        modifier_flags.set(
+            CharacterTableModifierFlags::UsePixmapIndex,
+            any_populated(&self.characters, |character| {
+                character.pixmap_index.is_some()
+            }),
+        );
+        modifier_flags.set(
+            CharacterTableModifierFlags::UsePixmapTableIndex,
+            any_populated(&self.characters, |character| {
+                character.pixmap_table_index.is_some()
+            }),
+        );
+        self.modifier_flags = modifier_flags;
+
+        self.configuration_flags = CharacterTableConfigurationFlags::empty();
+        self.configuration_flags.set(
+            CharacterTableConfigurationFlags::ConstantCodePointCount,
+            self.constant_code_point_count.is_some(),
+        );
+
+        self.link_flags = CharacterTableLinkFlags::empty();
+        self.link_flags.set(
+            CharacterTableLinkFlags::LinkPixmapTables,
+            self.pixmap_table_indexes.is_some(),
+        );
+    }
+}
+
+impl PixmapTable {
+    pub(crate) fn normalize(&mut self) {
+        self.configuration_flags = PixmapTableConfigurationFlags::empty();
+        self.configuration_flags.set(
+            PixmapTableConfigurationFlags::ConstantWidth,
+            self.constant_width.is_some(),
+        );
+        self.configuration_flags.set(
+            PixmapTableConfigurationFlags::ConstantHeight,
+            self.constant_height.is_some(),
+        );
+        self.configuration_flags.set(
+            PixmapTableConfigurationFlags::ConstantBitsPerPixel,
+            self.constant_bits_per_pixel.is_some(),
+        );
+
+        self.link_flags = PixmapTableLinkFlags::empty();
+        self.link_flags.set(
+            PixmapTableLinkFlags::LinkColorTables,
+            self.color_table_indexes.is_some(),
+        );
+    }
+}
+
+impl ColorTable {
+    pub(crate) fn normalize(&mut self) {
+        self.modifier_flags = ColorTableModifierFlags::empty();
+        self.modifier_flags.set(
+            ColorTableModifierFlags::UseColorType,
+            any_populated(&self.colors, |color| color.color_type.is_some()),
+        );
+
+        self.configuration_flags = ColorTableConfigurationFlags::empty();
+        self.configuration_flags.set(
+            ColorTableConfigurationFlags::ConstantAlpha,
+            self.constant_alpha.is_some(),
+        );
+    }
+}
+
+impl FontTable {
+    pub(crate) fn normalize(&mut self) {
+        self.link_flags = FontTableLinkFlags::empty();
+        self.link_flags.set(
+            FontTableLinkFlags::LinkCharacterTables,
+            self.character_table_indexes.is_some(),
+        );
+    }
+}
+
+impl Layout {
+    /// Recomputes every modifier, configuration and link flag in this layout from which
+    /// `Option` fields are populated, clearing any unknown flag bits.
+    ///
+    /// Configuration and link flags are set exactly when their table-level value is `Some`.
+    /// A per-record modifier flag (such as [`CharacterTableModifierFlags::UseAdvanceX`]) is
+    /// set when any record in the table provides the field, so no value is dropped. A
+    /// partially populated table then fails to serialize with
+    /// [`SerializeError::MissingField`] for the first record without the field, and
+    /// [`Layout::validate`] reports every such record.
+    pub fn normalize(&mut self) {
+        self.character_tables
+            .iter_mut()
+            .for_each(CharacterTable::normalize);
+        self.pixmap_tables
+            .iter_mut()
+            .for_each(PixmapTable::normalize);
+        self.color_tables.iter_mut().for_each(ColorTable::normalize);
+        self.font_tables.iter_mut().for_each(FontTable::normalize);
+    }
+}
@@ -0,0 +1,259 @@
+/*
+ * Copyright 2025 SimplePixelFont
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::core::tables::flagged;
+use crate::core::tables::pixmap::{pack_pixels, pixmap_byte_length, read_pixel};
+use crate::core::*;
+use crate::Vec;
+
+/// Returns the value every item shares, or `None` if they differ or there are none.
+fn shared<T: PartialEq>(mut values: impl Iterator<Item = T>) -> Option<T> {
+    let first = values.next()?;
+    values.all(|value| value == first).then_some(first)
+}
+
+/// Number of bits `total_bits` of pixel data are stored in, which is rounded up to a full
+/// byte unless the layout is compact.
+fn stored_bits(total_bits: usize, compact: bool) -> usize {
+    if compact {
+        total_bits
+    } else {
+        total_bits.div_ceil(8) * 8
+    }
+}
+
+/// A pixmap's size, pixel values and the fewest bits per pixel that can hold them.
+struct PixmapPlan {
+    width: u8,
+    height: u8,
+    pixels: Vec<u8>,
+    bits_per_pixel: u8,
+}
+
+impl PixmapPlan {
+    fn stored_bits(&self, bits_per_pixel: u8, compact: bool) -> usize {
+        stored_bits(self.pixels.len() * bits_per_pixel as usize, compact)
+    }
+}
+
+/// Picks the constant bits per pixel to store `plans` with, or `None` to give every pixmap
+/// its own, whichever takes fewer bits. Also returns how many bits that is.
+fn choose_bits_per_pixel(plans: &[PixmapPlan], compact: bool) -> (Option<u8>, usize) {
+    let Some(widest) = plans.iter().map(|plan| plan.bits_per_pixel).max() else {
+        return (None, 0);
+    };
+    let constant = 8 + plans
+        .iter()
+        .map(|plan| plan.stored_bits(widest, compact))
+        .sum::<usize>();
+    let custom = plans
+        .iter()
+        .map(|plan| 8 + plan.stored_bits(plan.bits_per_pixel, compact))
+        .sum::<usize>();
+
+    if constant <= custom {
+        (Some(widest), constant)
+    } else {
+        (None, custom)
+    }
+}
+
+impl PixmapTable {
+    /// Reads every pixmap of this table, or returns `None` if any of them is not valid, in
+    /// which case the table is left as is.
+    fn plans(&self) -> Option<Vec<PixmapPlan>> {
+        self.pixmaps
+            .iter()
+            .map(|pixmap| {
+                let (width, height, bits_per_pixel) = self.pixmap_dimensions(pixmap)?;
+                if !(1..=8).contains(&bits_per_pixel)
+                    || pixmap.data.len() != pixmap_byte_length(width, height, bits_per_pixel)
+                {
+                    return None;
+                }
+                let pixels: Vec<u8> = (0..width as usize * height as usize)
+                    .map(|index| read_pixel(&pixmap.data, index, bits_per_pixel))
+                    .collect();
+                let brightest = pixels.iter().copied().max().unwrap_or(0);
+
+                Some(PixmapPlan {
+                    width,
+                    height,
+                    pixels,
+                    bits_per_pixel: (u8::BITS - brightest.leading_zeros()).max(1) as u8,
+                })
+            })
+            .collect()
+    }
+
+    fn optimize(&mut self, plans: Vec<PixmapPlan>, compact: bool) {
+        self.constant_width = shared(plans.iter().map(|plan| plan.width));
+        self.constant_height = shared(plans.iter().map(|plan| plan.height));
+        (self.constant_bits_per_pixel, _) = choose_bits_per_pixel(&plans, compact);
+
+        for (pixmap, plan) in self.pixmaps.iter_mut().zip(plans) {
+            let bits_per_pixel = self.constant_bits_per_pixel.unwrap_or(plan.bits_per_pixel);
+            pixmap.custom_width = self.constant_width.is_none().then_some(plan.width);
+            pixmap.custom_height = self.constant_height.is_none().then_some(plan.height);
+            pixmap.custom_bits_per_pixel = self
+                .constant_bits_per_pixel
+                .is_none()
+                .then_some(bits_per_pixel);
+            pixmap.data = pack_pixels(&plan.pixels, bits_per_pixel);
+        }
+    }
+}
+
+impl CharacterTable {
+    /// Returns a copy of this table that resolves every character to the same pixmap and
+    /// advance, storing as little as possible.
+    fn optimized(&self, layout: &Layout) -> CharacterTable {
+        let resolved = |table: &CharacterTable| -> Vec<_> {
+            (0..table.characters.len())
+                .map(|index| {
+                    table.resolve(layout, index).map(|resolved| {
+                        (
+                            resolved.pixmap_table_index,
+                            resolved.pixmap_index,
+                            resolved.advance_x,
+                        )
+                    })
+                })
+                .collect()
+        };
+        let expected = resolved(self);
+
+        let mut table = self.clone();
+        table.pixmap_table_indexes = self.effective_links().map(<[u8]>::to_vec);
+        table.constant_code_point_count = shared(
+            self.characters
+                .iter()
+                .map(|character| character.code_points.chars().count()),
+        )
+        .and_then(|count| u8::try_from(count).ok())
+        .filter(|count| *count > 0);
+        for character in &mut table.characters {
+            character.advance_x = flagged(
+                self.modifier_flags
+                    .contains(CharacterTableModifierFlags::UseAdvanceX),
+                character.advance_x,
+            );
+            character.pixmap_index = flagged(
+                self.modifier_flags
+                    .contains(CharacterTableModifierFlags::UsePixmapIndex),
+                character.pixmap_index,
+            );
+            character.pixmap_table_index = flagged(
+                self.modifier_flags
+                    .contains(CharacterTableModifierFlags::UsePixmapTableIndex),
+                character.pixmap_table_index,
+            );
+        }
+        table.normalize();
+
+        // Drop each per-character field the defaults reproduce: an advance equal to the
+        // pixmap width, a pixmap index equal to the record index, or a pixmap table index
+        // that searching the linked tables in order would find anyway.
+        let clears: [fn(&mut Character); 3] = [
+            |character| character.advance_x = None,
+            |character| character.pixmap_index = None,
+            |character| character.pixmap_table_index = None,
+        ];
+        for clear in clears {
+            let mut candidate = table.clone();
+            candidate.characters.iter_mut().for_each(clear);
+            candidate.normalize();
+            if resolved(&candidate) == expected {
+                table = candidate;
+            }
+        }
+
+        if resolved(&table) == expected {
+            table
+        } else {
+            self.clone()
+        }
+    }
+}
+
+impl ColorTable {
+    fn optimize(&mut self) {
+        for index in 0..self.colors.len() {
+            self.colors[index].color_type = self.color_type(&self.colors[index]);
+        }
+
+        let Some(alphas) = self
+            .colors
+            .iter()
+            .map(|color| self.alpha(color))
+            .collect::<Option<Vec<u8>>>()
+        else {
+            return;
+        };
+        self.constant_alpha = shared(alphas.iter().copied());
+        for (color, alpha) in self.colors.iter_mut().zip(alphas) {
+            color.custom_alpha = self.constant_alpha.is_none().then_some(alpha);
+        }
+    }
+}
+
+impl Layout {
+    /// Rewrites this layout into the encoding that serializes to the fewest bytes while
+    /// rendering the same glyphs, see [`Layout::semantically_eq`].
+    ///
+    /// A width, height, bits per pixel, alpha or code point count shared by every record of
+    /// a table becomes a table constant, and is stored per record otherwise. Pixels are
+    /// repacked in the fewest bits that hold their values, per-character fields that match
+    /// their defaults are dropped, values whose flag is unset are cleared, and every flag is
+    /// recomputed with [`Layout::normalize`]. The layout is made [`Layout::compact`] when
+    /// that saves bytes, which [`LayoutRef`] cannot read. A pixmap table holding a pixmap
+    /// with missing dimensions or the wrong amount of data keeps its encoding.
+    pub fn optimize(&mut self) {
+        let plans: Vec<Option<Vec<PixmapPlan>>> =
+            self.pixmap_tables.iter().map(PixmapTable::plans).collect();
+        let pixmap_bits = |compact| {
+            plans
+                .iter()
+                .flatten()
+                .map(|plans| choose_bits_per_pixel(plans, compact).1)
+                .sum::<usize>()
+        };
+        let (compact_bits, padded_bits) = (pixmap_bits(true), pixmap_bits(false));
+        // A pixmap kept as is may set bits past its last pixel, which compact layouts
+        // cannot store.
+        if compact_bits < padded_bits && plans.iter().all(Option::is_some) {
+            self.compact = true;
+        } else if compact_bits == padded_bits {
+            self.compact = false;
+        }
+
+        for (table, plans) in self.pixmap_tables.iter_mut().zip(plans) {
+            table.color_table_indexes = table.effective_links().map(<[u8]>::to_vec);
+            if let Some(plans) = plans {
+                table.optimize(plans, self.compact);
+            }
+        }
+        for index in 0..self.character_tables.len() {
+            self.character_tables[index] = self.character_tables[index].optimized(self);
+        }
+        self.color_tables.iter_mut().for_each(ColorTable::optimize);
+        for table in &mut self.font_tables {
+            table.character_table_indexes = table.effective_links().map(<[u8]>::to_vec);
+        }
+
+        self.normalize();
+    }
+}
@@ -0,0 +1,320 @@
+/*
+ * Copyright 2025 SimplePixelFont
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::core::*;
+use crate::{Map, Vec};
+
+/// Returned by [`Layout::resolve_font`] when the font does not exist or one of the links it
+/// depends on does not point at anything.
+#[non_exhaustive]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ResolveError {
+    /// No font in any font table has the requested name.
+    FontNotFound,
+    /// A link stored in a table or record refers to a table that does not exist.
+    DanglingLink {
+        /// The kind of table the link is stored in.
+        table_type: TableType,
+        /// Index of that table within its [`Layout`] vector.
+        table_index: usize,
+        /// Index of the record the link is stored in, or `None` for a table-level link.
+        record_index: Option<usize>,
+        /// The kind of table the link refers to.
+        target: TableType,
+        /// The link value that does not resolve.
+        index: usize,
+    },
+    /// A character's pixmap index or pixmap table index does not lead to a pixmap with
+    /// resolvable dimensions.
+    MissingPixmap {
+        /// Index of the character table within [`Layout::character_tables`].
+        table_index: usize,
+        /// Index of the character within that table.
+        record_index: usize,
+    },
+}
+
+impl core::fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ResolveError::FontNotFound => write!(f, "font not found"),
+            ResolveError::DanglingLink {
+                table_type,
+                table_index,
+                record_index,
+                target,
+                index,
+            } => {
+                write!(f, "{} {}", table_type, table_index)?;
+                if let Some(record_index) = record_index {
+                    write!(f, ", {} {}", table_type.record_name(), record_index)?;
+                }
+                write!(f, " links {} {}, which does not exist", target, index)
+            }
+            ResolveError::MissingPixmap {
+                table_index,
+                record_index,
+            } => write!(
+                f,
+                "{} {}, {} {} has no pixmap",
+                TableType::Character,
+                table_index,
+                TableType::Character.record_name(),
+                record_index
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ResolveError {}
+
+/// A color of a [`Palette`], with the alpha and color type its [`ColorTable`] sets for every
+/// color already applied.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ResolvedColor {
+    /// The color type, [`ColorType::Dynamic`] if the table does not store one.
+    pub color_type: ColorType,
+    /// The table's constant alpha, or else the color's custom alpha, or else fully opaque.
+    pub alpha: u8,
+    /// See [`Color::red`].
+    pub red: u8,
+    /// See [`Color::green`].
+    pub green: u8,
+    /// See [`Color::blue`].
+    pub blue: u8,
+}
+
+impl ResolvedColor {
+    fn new(table: &ColorTable, color: &Color) -> Self {
+        ResolvedColor {
+            color_type: table.color_type(color).unwrap_or_default(),
+            alpha: table.alpha(color).unwrap_or(u8::MAX),
+            red: color.red,
+            green: color.green,
+            blue: color.blue,
+        }
+    }
+}
+
+/// The colors a pixmap's pixel values index into: the colors of every color table its
+/// [`PixmapTable`] links, in link order.
+#[derive(Debug, Clone)]
+pub struct Palette {
+    colors: Vec<ResolvedColor>,
+}
+
+impl Palette {
+    /// Resolves the colors of the color tables at `links`, skipping links to missing tables.
+    fn new(layout: &Layout, links: &[u8]) -> Self {
+        Palette {
+            colors: links
+                .iter()
+                .filter_map(|link| layout.color_tables.get(*link as usize))
+                .flat_map(|table| {
+                    table
+                        .colors
+                        .iter()
+                        .map(|color| ResolvedColor::new(table, color))
+                })
+                .collect(),
+        }
+    }
+
+    /// Returns the color for pixel value `value`, or `None` if the palette is too short.
+    pub fn get(&self, value: u8) -> Option<ResolvedColor> {
+        self.colors.get(value as usize).copied()
+    }
+
+    /// Iterates over every color of the palette in pixel value order.
+    pub fn iter(&self) -> impl Iterator<Item = ResolvedColor> + '_ {
+        self.colors.iter().copied()
+    }
+
+    /// The number of colors in the palette.
+    pub fn len(&self) -> usize {
+        self.colors.len()
+    }
+
+    /// Returns `true` if the pixmap table links no colors.
+    pub fn is_empty(&self) -> bool {
+        self.colors.is_empty()
+    }
+}
+
+/// A glyph of a [`ResolvedFont`]: its character and pixmap, and the palette its pixel values
+/// index into.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy)]
+pub struct ResolvedGlyph<'a> {
+    /// The character and the pixmap it renders with.
+    pub character: ResolvedCharacter<'a>,
+    /// The colors linked by the pixmap's table, shared by every glyph of that table.
+    pub palette: &'a Palette,
+}
+
+impl ResolvedGlyph<'_> {
+    /// Returns the color of the pixel at column `x` and row `y`, or `None` if there is no
+    /// such pixel or its value is past the end of the palette.
+    pub fn color(&self, x: u8, y: u8) -> Option<ResolvedColor> {
+        let value = self
+            .character
+            .pixmap_table
+            .pixel(self.character.pixmap_index, x, y)?;
+        self.palette.get(value)
+    }
+}
+
+/// A [`Font`] with every link it depends on followed and checked, created by
+/// [`Layout::resolve_font`].
+#[derive(Debug, Clone)]
+pub struct ResolvedFont<'a> {
+    font: &'a Font,
+    characters: CharacterIndex<'a>,
+    /// The palette of every pixmap table the characters link, by pixmap table index.
+    palettes: Map<usize, Palette>,
+}
+
+impl<'a> ResolvedFont<'a> {
+    /// The font record.
+    pub fn font(&self) -> &'a Font {
+        self.font
+    }
+
+    /// The characters of every character table linked by the font.
+    pub fn characters(&self) -> &CharacterIndex<'a> {
+        &self.characters
+    }
+
+    fn glyph_for(&self, character: ResolvedCharacter<'a>) -> Option<ResolvedGlyph<'_>> {
+        Some(ResolvedGlyph {
+            character,
+            palette: self.palettes.get(&character.pixmap_table_index)?,
+        })
+    }
+
+    /// Finds the glyph for the single `code_point`, see [`CharacterIndex::get`].
+    pub fn glyph(&self, code_point: char) -> Option<ResolvedGlyph<'_>> {
+        self.glyph_for(self.characters.get(code_point)?)
+    }
+
+    /// Finds the glyph for `cluster`, see [`CharacterIndex::get_cluster`].
+    pub fn cluster(&self, cluster: &str) -> Option<ResolvedGlyph<'_>> {
+        self.glyph_for(self.characters.get_cluster(cluster)?)
+    }
+
+    /// Splits `text` into the glyphs of this font that render it, matching the longest
+    /// cluster at each position, see [`CharacterIndex::segment`]. A code point the font does
+    /// not map is rendered with its glyph for [`char::REPLACEMENT_CHARACTER`], if any.
+    pub fn segment<'t>(&self, text: &'t str) -> Vec<GlyphRef<'a, 't>> {
+        self.characters
+            .segment(text, Some(char::REPLACEMENT_CHARACTER))
+    }
+}
+
+/// Returns the target of link `index`, or a [`ResolveError::DanglingLink`] if there is none.
+fn follow<T>(
+    targets: &[T],
+    index: usize,
+    target: TableType,
+    (table_type, table_index, record_index): (TableType, usize, Option<usize>),
+) -> Result<&T, ResolveError> {
+    targets.get(index).ok_or(ResolveError::DanglingLink {
+        table_type,
+        table_index,
+        record_index,
+        target,
+        index,
+    })
+}
+
+impl Layout {
+    /// Finds the first font named `name` and follows its links to character, pixmap and
+    /// color tables, failing if any of them, or the pixmap of any character, does not exist.
+    pub fn resolve_font(&self, name: &str) -> Result<ResolvedFont<'_>, ResolveError> {
+        let (font_table_index, font_table, font_index, font) = self
+            .font_tables
+            .iter()
+            .enumerate()
+            .find_map(|(table_index, table)| {
+                let (font_index, font) = table
+                    .fonts
+                    .iter()
+                    .enumerate()
+                    .find(|(_, font)| font.name == name)?;
+                Some((table_index, table, font_index, font))
+            })
+            .ok_or(ResolveError::FontNotFound)?;
+
+        let font_links = font_table.effective_links().unwrap_or_default();
+        let mut character_tables = Vec::new();
+        for linked in &font.linked_character_table_indexes {
+            let link = follow(
+                font_links,
+                *linked as usize,
+                TableType::Character,
+                (TableType::Font, font_table_index, Some(font_index)),
+            )?;
+            follow(
+                &self.character_tables,
+                *link as usize,
+                TableType::Character,
+                (TableType::Font, font_table_index, None),
+            )?;
+            character_tables.push(*link as usize);
+        }
+
+        let mut palettes = Map::new();
+        for table_index in &character_tables {
+            let table = &self.character_tables[*table_index];
+            for link in table.effective_links().unwrap_or_default() {
+                let pixmap_table = follow(
+                    &self.pixmap_tables,
+                    *link as usize,
+                    TableType::Pixmap,
+                    (TableType::Character, *table_index, None),
+                )?;
+                let color_links = pixmap_table.effective_links().unwrap_or_default();
+                for color_link in color_links {
+                    follow(
+                        &self.color_tables,
+                        *color_link as usize,
+                        TableType::Color,
+                        (TableType::Pixmap, *link as usize, None),
+                    )?;
+                }
+                palettes
+                    .entry(*link as usize)
+                    .or_insert_with(|| Palette::new(self, color_links));
+            }
+            for record_index in 0..table.characters.len() {
+                if table.resolve(self, record_index).is_none() {
+                    return Err(ResolveError::MissingPixmap {
+                        table_index: *table_index,
+                        record_index,
+                    });
+                }
+            }
+        }
+
+        Ok(ResolvedFont {
+            font,
+            characters: CharacterIndex::from_tables(self, character_tables),
+            palettes,
+        })
+    }
+}
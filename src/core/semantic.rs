@@ -0,0 +1,109 @@
+/*
+ * Copyright 2025 SimplePixelFont
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::core::tables::pixmap::read_pixel;
+use crate::core::*;
+
+fn tables_eq<T>(a: &[T], b: &[T], table_eq: impl Fn(&T, &T) -> bool) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(a, b)| table_eq(a, b))
+}
+
+impl PixmapTable {
+    /// Compares the size and pixel values of two pixmaps, ignoring how many bits each pixel
+    /// is stored in and any padding bits after the last pixel. Pixmaps whose dimensions do
+    /// not resolve, or whose bits per pixel are not within `1`-`8`, are only equal if their
+    /// dimensions and data bytes are.
+    fn pixmap_eq(&self, pixmap: &Pixmap, other_table: &PixmapTable, other: &Pixmap) -> bool {
+        let dimensions = self.pixmap_dimensions(pixmap);
+        let other_dimensions = other_table.pixmap_dimensions(other);
+        match (dimensions, other_dimensions) {
+            (
+                Some((width, height, bits_per_pixel)),
+                Some((other_width, other_height, other_bits)),
+            ) if (1..=8).contains(&bits_per_pixel) && (1..=8).contains(&other_bits) => {
+                width == other_width
+                    && height == other_height
+                    && (0..width as usize * height as usize).all(|index| {
+                        read_pixel(&pixmap.data, index, bits_per_pixel)
+                            == read_pixel(&other.data, index, other_bits)
+                    })
+            }
+            _ => dimensions == other_dimensions && pixmap.data == other.data,
+        }
+    }
+}
+
+impl Layout {
+    /// Returns `true` if both layouts render the same glyphs with the same metrics, colors
+    /// and font information, even if they are represented differently.
+    ///
+    /// Unlike `==`, this ignores the [`Layout::version`] and [`Layout::compact`] flag,
+    /// whether a width, height, bits per pixel or alpha is stored as a table constant or
+    /// per record, how many bits each pixel is stored in, padding bits after the last
+    /// pixel, and record fields whose modifier flag is not set.
+    pub fn semantically_eq(&self, other: &Layout) -> bool {
+        tables_eq(&self.character_tables, &other.character_tables, |a, b| {
+            self.character_table_eq(a, other, b)
+        }) && tables_eq(&self.pixmap_tables, &other.pixmap_tables, |a, b| {
+            a.effective_links() == b.effective_links()
+                && tables_eq(&a.pixmaps, &b.pixmaps, |pixmap, other_pixmap| {
+                    a.pixmap_eq(pixmap, b, other_pixmap)
+                })
+        }) && tables_eq(&self.color_tables, &other.color_tables, |a, b| {
+            tables_eq(&a.colors, &b.colors, |color, other_color| {
+                a.color_type(color) == b.color_type(other_color)
+                    && a.alpha(color) == b.alpha(other_color)
+                    && (color.red, color.green, color.blue)
+                        == (other_color.red, other_color.green, other_color.blue)
+            })
+        }) && tables_eq(&self.font_tables, &other.font_tables, |a, b| {
+            a.effective_links() == b.effective_links() && a.fonts == b.fonts
+        }) && self.unknown_tables == other.unknown_tables
+    }
+
+    fn character_table_eq(
+        &self,
+        table: &CharacterTable,
+        other: &Layout,
+        other_table: &CharacterTable,
+    ) -> bool {
+        table.effective_links() == other_table.effective_links()
+            && table.characters.len() == other_table.characters.len()
+            && table
+                .characters
+                .iter()
+                .zip(&other_table.characters)
+                .enumerate()
+                .all(|(index, (character, other_character))| {
+                    character.code_points == other_character.code_points
+                        && match (
+                            table.resolve(self, index),
+                            other_table.resolve(other, index),
+                        ) {
+                            (Some(resolved), Some(other_resolved)) => {
+                                resolved.advance_x == other_resolved.advance_x
+                                    && resolved.pixmap_table.pixmap_eq(
+                                        resolved.pixmap,
+                                        other_resolved.pixmap_table,
+                                        other_resolved.pixmap,
+                                    )
+                            }
+                            (None, None) => true,
+                            _ => false,
+                        }
+                })
+    }
+}
@@ -29,7 +29,6 @@ impl<'a, T: TagWriter> SerializeEngine<'a, T> {
             layout,
             #[cfg(feature = "tagging")]
             tags,
-            #[cfg(feature = "tagging")]
             tagging_data: TaggingData::default(),
             _phantom: PhantomData,
         }
@@ -39,6 +38,11 @@ impl<'a, T: TagWriter> SerializeEngine<'a, T> {
     pub fn tagging_engine(&mut self, tags: T) {
         self.tags = tags;
     }
+    /// Streams the serialized bytes to `sink` as they are encoded, instead of collecting
+    /// them in the engine. [`SerializeEngine::data`] then only holds bytes not yet written.
+    pub fn set_sink(&mut self, sink: &'a mut dyn byte::ByteSink) {
+        self.bytes.set_sink(sink);
+    }
     /// Returns the bytes serialized so far.
     pub fn data(&self) -> &[u8] {
         &self.bytes.bytes
@@ -49,6 +53,71 @@ impl<'a, T: TagWriter> SerializeEngine<'a, T> {
     }
 }
 
+impl<T: TagWriter> SerializeEngine<'_, T> {
+    /// Returns `true` if the version being written can express `feature`.
+    pub(crate) fn supports(&self, feature: VersionFeature) -> bool {
+        self.layout.version.supports(feature)
+    }
+
+    /// Writes a flags byte whose defined bits are `known`. Any other bit is cleared in
+    /// versions supporting [`VersionFeature::ZeroReservedBits`].
+    pub(crate) fn push_flags(&mut self, value: u8, known: u8) {
+        if self.supports(VersionFeature::ZeroReservedBits) {
+            self.bytes.push(value & known);
+        } else {
+            self.bytes.push(value);
+        }
+    }
+
+    /// Builds a [`SerializeError::MissingField`] for a table-level `field` of the table being serialized.
+    pub(crate) fn missing_table_field(
+        &self,
+        table_type: TableType,
+        field: &'static str,
+    ) -> SerializeError {
+        SerializeError::MissingField {
+            table_type,
+            table_index: self.tagging_data.current_table_index as usize,
+            record_index: None,
+            field,
+        }
+    }
+    /// Checks that a table-level `field` of the table being serialized is present exactly
+    /// when its flag is set, as the field is written whenever it is present. Fails with
+    /// [`SerializeError::MissingField`] or [`SerializeError::UnflaggedField`] otherwise.
+    pub(crate) fn check_table_field(
+        &self,
+        table_type: TableType,
+        field: &'static str,
+        flag_set: bool,
+        field_set: bool,
+    ) -> Result<(), SerializeError> {
+        match (flag_set, field_set) {
+            (true, false) => Err(self.missing_table_field(table_type, field)),
+            (false, true) => Err(SerializeError::UnflaggedField {
+                table_type,
+                table_index: self.tagging_data.current_table_index as usize,
+                record_index: None,
+                field,
+            }),
+            _ => Ok(()),
+        }
+    }
+    /// Builds a [`SerializeError::MissingField`] for `field` of the record being serialized.
+    pub(crate) fn missing_record_field(
+        &self,
+        table_type: TableType,
+        field: &'static str,
+    ) -> SerializeError {
+        SerializeError::MissingField {
+            table_type,
+            table_index: self.tagging_data.current_table_index as usize,
+            record_index: Some(self.tagging_data.current_record_index as usize),
+            field,
+        }
+    }
+}
+
 impl<'a> SerializeEngine<'a> {
     /// Creates a [`SerializeEngine`] for `layout`, without tag tracking.
     pub fn from_layout(layout: &'a Layout) -> Self {
@@ -57,7 +126,6 @@ impl<'a> SerializeEngine<'a> {
             layout,
             #[cfg(feature = "tagging")]
             tags: TagWriterNoOp,
-            #[cfg(feature = "tagging")]
             tagging_data: TaggingData::default(),
             _phantom: PhantomData,
         }
@@ -118,3 +186,36 @@ pub(crate) fn push_header<T: TagWriter>(engine: &mut SerializeEngine<T>) {
     #[cfg(feature = "log")]
     info!("Pushed header");
 }
+
+pub(crate) fn push_raw_table<T: TagWriter>(
+    engine: &mut SerializeEngine<T>,
+    raw_table: &RawTable,
+) -> Result<(), SerializeError> {
+    if raw_table.identifier < RawTable::MIN_IDENTIFIER {
+        return Err(SerializeError::UnsupportedTableIdentifier {
+            value: raw_table.identifier,
+        });
+    }
+    let length =
+        u32::try_from(raw_table.data.len()).map_err(|_| SerializeError::StaticVectorTooLarge)?;
+
+    #[cfg(feature = "tagging")]
+    let start = engine.bytes.byte_index();
+
+    engine.bytes.push(raw_table.identifier);
+    engine.bytes.write_bits(length, 32);
+    for byte in &raw_table.data {
+        engine.bytes.push(*byte);
+    }
+
+    #[cfg(feature = "tagging")]
+    engine.tags.tag_span(
+        TagKind::RawTable {
+            index: engine.tagging_data.current_table_index,
+            identifier: raw_table.identifier,
+        },
+        Span::new(start, engine.bytes.byte_index()),
+    );
+
+    Ok(())
+}
@@ -0,0 +1,204 @@
+/*
+ * Copyright 2025 SimplePixelFont
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::core::*;
+use crate::Vec;
+
+/// The most records or links a table can hold, as their counts are stored in a single byte.
+const MAX_RECORDS: usize = 255;
+
+/// Splits `records` past the first [`MAX_RECORDS`] into chunks of at most that many.
+fn split_off_shards<T>(records: &mut Vec<T>) -> Vec<Vec<T>> {
+    let mut shards = Vec::new();
+    if records.len() <= MAX_RECORDS {
+        return shards;
+    }
+
+    let mut rest = records.split_off(MAX_RECORDS);
+    while !rest.is_empty() {
+        let tail = rest.split_off(rest.len().min(MAX_RECORDS));
+        shards.push(rest);
+        rest = tail;
+    }
+    shards
+}
+
+/// Splits every table in `tables` holding more than [`MAX_RECORDS`] records, keeping the
+/// first records in place and appending a copy of the table for each further chunk. Returns
+/// the indexes of the tables appended for each original table.
+fn shard_tables<T: Clone, R>(
+    tables: &mut Vec<T>,
+    records: impl Fn(&mut T) -> &mut Vec<R>,
+) -> Result<Vec<Vec<u8>>, SerializeError> {
+    let mut appended = Vec::new();
+    for index in 0..tables.len() {
+        let mut shard_indexes = Vec::new();
+        for shard_records in split_off_shards(records(&mut tables[index])) {
+            let mut shard = tables[index].clone();
+            *records(&mut shard) = shard_records;
+            shard_indexes.push(
+                u8::try_from(tables.len()).map_err(|_| SerializeError::StaticVectorTooLarge)?,
+            );
+            tables.push(shard);
+        }
+        appended.push(shard_indexes);
+    }
+    Ok(appended)
+}
+
+/// Follows every link in `links` with links to the tables appended for it by
+/// [`shard_tables`], so the linked records keep their order.
+fn expand_links(links: &[u8], appended: &[Vec<u8>]) -> Result<Vec<u8>, SerializeError> {
+    let expanded: Vec<u8> = links
+        .iter()
+        .flat_map(|link| {
+            core::iter::once(*link)
+                .chain(appended.get(*link as usize).into_iter().flatten().copied())
+        })
+        .collect();
+
+    if expanded.len() > MAX_RECORDS {
+        return Err(SerializeError::StaticVectorTooLarge);
+    }
+    Ok(expanded)
+}
+
+impl Layout {
+    /// Splits every [`CharacterTable`], [`PixmapTable`] and [`ColorTable`] holding more than
+    /// 255 records, which a single table cannot store, into as many tables as needed.
+    ///
+    /// The first 255 records stay in place and the rest move to copies of the table
+    /// appended after the existing ones, so no existing table index changes. Every link to
+    /// a split table is followed by links to its new tables, which keeps the palettes of
+    /// pixmap tables and the characters of fonts in order, and
+    /// [`Font::linked_character_table_indexes`] is rewritten to match. Characters of a split
+    /// character table, or of one linking a split pixmap table, get an explicit pixmap index
+    /// and pixmap table index.
+    ///
+    /// Fails with [`SerializeError::UnresolvedCharacter`] if one of those characters has no
+    /// pixmap, and with [`SerializeError::StaticVectorTooLarge`] if the result would need
+    /// more than 256 tables of a kind or more than 255 links in a table. The layout is left
+    /// unchanged on error.
+    pub fn shard(&mut self) -> Result<(), SerializeError> {
+        let mut layout = self.clone();
+
+        // Resolve the characters that need explicit indexes before any pixmap moves.
+        let is_split = |link: &u8| {
+            self.pixmap_tables
+                .get(*link as usize)
+                .is_some_and(|table| table.pixmaps.len() > MAX_RECORDS)
+        };
+        let resolved = self
+            .character_tables
+            .iter()
+            .enumerate()
+            .map(|(table_index, table)| {
+                let links = table.effective_links().unwrap_or_default();
+                if table.characters.len() <= MAX_RECORDS && !links.iter().any(is_split) {
+                    return Ok(None);
+                }
+
+                (0..table.characters.len())
+                    .map(|record_index| {
+                        table
+                            .resolve(self, record_index)
+                            .map(|resolved| (resolved.pixmap_table_index, resolved.pixmap_index))
+                            .ok_or(SerializeError::UnresolvedCharacter {
+                                table_index,
+                                record_index,
+                            })
+                    })
+                    .collect::<Result<Vec<_>, _>>()
+                    .map(Some)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let color_shards = shard_tables(&mut layout.color_tables, |table| &mut table.colors)?;
+        for table in &mut layout.pixmap_tables {
+            if let Some(links) = &table.color_table_indexes {
+                table.color_table_indexes = Some(expand_links(links, &color_shards)?);
+            }
+        }
+
+        let pixmap_shards = shard_tables(&mut layout.pixmap_tables, |table| &mut table.pixmaps)?;
+        for (table_index, resolved) in resolved.into_iter().enumerate() {
+            // Other character tables only link pixmap tables that were not split.
+            let Some(resolved) = resolved else {
+                continue;
+            };
+
+            let table = &mut layout.character_tables[table_index];
+            let links = expand_links(table.effective_links().unwrap_or_default(), &pixmap_shards)?;
+            for (record_index, (character, (pixmap_table_index, pixmap_index))) in
+                table.characters.iter_mut().zip(resolved).enumerate()
+            {
+                let shard = match pixmap_index / MAX_RECORDS {
+                    0 => pixmap_table_index as u8,
+                    shard => pixmap_shards[pixmap_table_index][shard - 1],
+                };
+                let position = links.iter().position(|link| *link == shard).ok_or(
+                    SerializeError::UnresolvedCharacter {
+                        table_index,
+                        record_index,
+                    },
+                )?;
+                character.pixmap_table_index = Some(position as u8);
+                character.pixmap_index = Some((pixmap_index % MAX_RECORDS) as u8);
+            }
+            table.pixmap_table_indexes = Some(links);
+            table.link_flags |= CharacterTableLinkFlags::LinkPixmapTables;
+            table.modifier_flags |= CharacterTableModifierFlags::UsePixmapIndex
+                | CharacterTableModifierFlags::UsePixmapTableIndex;
+        }
+
+        let character_shards =
+            shard_tables(&mut layout.character_tables, |table| &mut table.characters)?;
+        for table in &mut layout.font_tables {
+            let Some(links) = &table.character_table_indexes else {
+                continue;
+            };
+            let expanded = expand_links(links, &character_shards)?;
+
+            // Where each original link and the links following it for its shards start.
+            let mut positions = Vec::with_capacity(links.len());
+            let mut position = 0;
+            for link in links {
+                let count = 1 + character_shards.get(*link as usize).map_or(0, Vec::len);
+                positions.push(position..position + count);
+                position += count;
+            }
+
+            for font in &mut table.fonts {
+                let mut linked = Vec::new();
+                for index in &font.linked_character_table_indexes {
+                    match positions.get(*index as usize) {
+                        Some(range) => linked.extend(range.clone().map(|position| position as u8)),
+                        // Keep a dangling link dangling.
+                        None => linked.push(
+                            u8::try_from(*index as usize - links.len() + expanded.len())
+                                .map_err(|_| SerializeError::StaticVectorTooLarge)?,
+                        ),
+                    }
+                }
+                font.linked_character_table_indexes = linked;
+            }
+            table.character_table_indexes = Some(expanded);
+        }
+
+        *self = layout;
+        Ok(())
+    }
+}
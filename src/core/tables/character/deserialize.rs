@@ -17,7 +17,7 @@
 use crate::core::byte::ByteReader;
 use crate::core::{
     Character, CharacterTable, CharacterTableConfigurationFlags, CharacterTableLinkFlags,
-    CharacterTableModifierFlags, DeserializeEngine, DeserializeError, TagWriter,
+    CharacterTableModifierFlags, DeserializeEngine, DeserializeError, Limit, TagWriter,
 };
 use crate::{vec, String, Vec};
 
@@ -31,8 +31,10 @@ impl CharacterTable {
     pub(crate) fn next_modifer_flags<R: ByteReader, T: TagWriter>(
         &mut self,
         engine: &mut DeserializeEngine<R, T>,
-    ) {
-        self.modifier_flags = CharacterTableModifierFlags::from_bits_retain(engine.bytes.next());
+    ) -> Result<(), DeserializeError> {
+        self.modifier_flags = CharacterTableModifierFlags::from_bits_retain(
+            engine.next_flags(CharacterTableModifierFlags::all().bits())?,
+        );
         #[cfg(feature = "tagging")]
         engine.tags.tag_bitflag(
             TagKind::CharacterTableModifierFlags {
@@ -61,16 +63,19 @@ impl CharacterTable {
             ],
             engine.bytes.byte_index(),
         );
+
+        Ok(())
     }
     pub(crate) fn next_configurations<R: ByteReader, T: TagWriter>(
         &mut self,
         engine: &mut DeserializeEngine<R, T>,
-    ) {
+    ) -> Result<(), DeserializeError> {
         #[cfg(feature = "tagging")]
         let configurations_start = engine.bytes.byte_index();
 
-        self.configuration_flags =
-            CharacterTableConfigurationFlags::from_bits_retain(engine.bytes.next());
+        self.configuration_flags = CharacterTableConfigurationFlags::from_bits_retain(
+            engine.next_flags(CharacterTableConfigurationFlags::all().bits())?,
+        );
         let use_constant_code_point_count = self
             .configuration_flags
             .contains(CharacterTableConfigurationFlags::ConstantCodePointCount);
@@ -90,7 +95,7 @@ impl CharacterTable {
         #[cfg(feature = "tagging")]
         let configuration_values_start = engine.bytes.byte_index();
         if use_constant_code_point_count {
-            self.constant_code_point_count = Some(engine.bytes.next());
+            self.constant_code_point_count = Some(engine.bytes.next()?);
             #[cfg(feature = "tagging")]
             engine.tags.tag_byte(
                 TagKind::CharacterTableConstantCodePointCount {
@@ -116,6 +121,8 @@ impl CharacterTable {
                 Span::new(configurations_start, engine.bytes.byte_index()),
             );
         }
+
+        Ok(())
     }
     pub(crate) fn next_table_links<R: ByteReader, T: TagWriter>(
         &mut self,
@@ -124,7 +131,9 @@ impl CharacterTable {
         #[cfg(feature = "tagging")]
         let links_start = engine.bytes.byte_index();
 
-        self.link_flags = CharacterTableLinkFlags::from_bits_retain(engine.bytes.next());
+        self.link_flags = CharacterTableLinkFlags::from_bits_retain(
+            engine.next_flags(CharacterTableLinkFlags::all().bits())?,
+        );
         let link_pixmap_tables = self
             .link_flags
             .contains(CharacterTableLinkFlags::LinkPixmapTables);
@@ -145,7 +154,7 @@ impl CharacterTable {
             #[cfg(feature = "tagging")]
             let pixmap_tables_start = engine.bytes.byte_index();
 
-            let pixmap_tables_length = engine.bytes.next();
+            let pixmap_tables_length = engine.bytes.next()?;
             #[cfg(feature = "tagging")]
             engine.tags.tag_byte(
                 TagKind::CharacterTablePixmapTableIndexesLength {
@@ -160,7 +169,7 @@ impl CharacterTable {
 
             let mut pixmap_table_indexes = Vec::new();
             for _ in 0..pixmap_tables_length {
-                let link_index = engine.bytes.next();
+                let link_index = engine.bytes.next()?;
                 pixmap_table_indexes.push(link_index);
                 #[cfg(feature = "tagging")]
                 engine.tags.tag_byte(
@@ -201,13 +210,67 @@ impl CharacterTable {
         );
         Ok(())
     }
+
+    /// Reads the per-record fields enabled by this table's modifier flags into `character`.
+    pub(crate) fn next_character_modifiers<R: ByteReader, T: TagWriter>(
+        &self,
+        engine: &mut DeserializeEngine<R, T>,
+        character: &mut Character,
+    ) -> Result<(), DeserializeError> {
+        if self
+            .modifier_flags
+            .contains(CharacterTableModifierFlags::UseAdvanceX)
+        {
+            character.advance_x = Some(engine.bytes.next()?);
+            #[cfg(feature = "tagging")]
+            engine.tags.tag_byte(
+                TagKind::CharacterAdvanceX {
+                    table_index: engine.tagging_data.current_table_index,
+                    char_index: engine.tagging_data.current_record_index,
+                    value: character.advance_x.unwrap(),
+                },
+                engine.bytes.byte_index(),
+            );
+        }
+        if self
+            .modifier_flags
+            .contains(CharacterTableModifierFlags::UsePixmapIndex)
+        {
+            character.pixmap_index = Some(engine.bytes.next()?);
+            #[cfg(feature = "tagging")]
+            engine.tags.tag_byte(
+                TagKind::CharacterPixmapIndex {
+                    table_index: engine.tagging_data.current_table_index,
+                    char_index: engine.tagging_data.current_record_index,
+                    value: character.pixmap_index.unwrap(),
+                },
+                engine.bytes.byte_index(),
+            );
+        }
+        if self
+            .modifier_flags
+            .contains(CharacterTableModifierFlags::UsePixmapTableIndex)
+        {
+            character.pixmap_table_index = Some(engine.bytes.next()?);
+            #[cfg(feature = "tagging")]
+            engine.tags.tag_byte(
+                TagKind::CharacterPixmapTableIndex {
+                    table_index: engine.tagging_data.current_table_index,
+                    char_index: engine.tagging_data.current_record_index,
+                    value: character.pixmap_table_index.unwrap(),
+                },
+                engine.bytes.byte_index(),
+            );
+        }
+        Ok(())
+    }
 }
 
 pub(crate) fn next_code_points<R: ByteReader, T: TagWriter>(
     engine: &mut DeserializeEngine<R, T>,
     character: &mut Character,
     constant_code_point_count: Option<u8>,
-) {
+) -> Result<(), DeserializeError> {
     #[cfg(feature = "tagging")]
     let start = engine.bytes.byte_index();
 
@@ -216,41 +279,49 @@ pub(crate) fn next_code_points<R: ByteReader, T: TagWriter>(
     let mut codepoint_count = 0;
 
     while !end_cluster {
-        let utf81 = engine.bytes.next();
+        let invalid = DeserializeError::InvalidUtf8 {
+            table: engine.tagging_data.current_table_index,
+            record: engine.tagging_data.current_record_index,
+        };
+        let character_start = engine.bytes.byte_index();
+        let utf81 = engine.bytes.next()?;
         let mut utf8_bytes: [u8; 4] = [0, 0, 0, 0];
 
         if utf81 >> 7 == 0b00000000 {
             utf8_bytes[0] = utf81;
         } else if utf81 >> 5 == 0b00000110 {
             utf8_bytes[0] = utf81;
-            utf8_bytes[1] = engine.bytes.next();
+            utf8_bytes[1] = engine.bytes.next()?;
         } else if utf81 >> 4 == 0b00001110 {
             utf8_bytes[0] = utf81;
-            utf8_bytes[1] = engine.bytes.next();
-            utf8_bytes[2] = engine.bytes.next();
+            utf8_bytes[1] = engine.bytes.next()?;
+            utf8_bytes[2] = engine.bytes.next()?;
         } else if utf81 >> 3 == 0b00011110 {
             utf8_bytes[0] = utf81;
-            utf8_bytes[1] = engine.bytes.next();
-            utf8_bytes[2] = engine.bytes.next();
-            utf8_bytes[3] = engine.bytes.next();
+            utf8_bytes[1] = engine.bytes.next()?;
+            utf8_bytes[2] = engine.bytes.next()?;
+            utf8_bytes[3] = engine.bytes.next()?;
+        } else {
+            return Err(engine.error_at(character_start, invalid));
         }
 
-        code_points.push(
-            String::from_utf8(utf8_bytes.to_vec())
-                .unwrap()
-                .chars()
-                .next()
-                .unwrap(),
-        );
+        let character = core::str::from_utf8(&utf8_bytes)
+            .ok()
+            .and_then(|string| string.chars().next())
+            .ok_or_else(|| engine.error_at(character_start, invalid))?;
+        code_points.push(character);
         codepoint_count += 1;
+        engine
+            .check_limit(Limit::CodePoints, codepoint_count)
+            .map_err(|error| engine.error_at(character_start, error))?;
 
         if let Some(constant_code_point_count) = constant_code_point_count {
-            if codepoint_count == constant_code_point_count {
+            if codepoint_count == constant_code_point_count as usize {
                 end_cluster = true;
             }
-        } else if engine.bytes.get() == 0 {
+        } else if engine.bytes.get()? == 0 {
             end_cluster = true;
-            engine.bytes.next();
+            engine.bytes.next()?;
         }
     }
 
@@ -268,4 +339,6 @@ pub(crate) fn next_code_points<R: ByteReader, T: TagWriter>(
     info!("Identified code points: {:?}", code_points);
 
     character.code_points = code_points;
+
+    Ok(())
 }
@@ -14,12 +14,13 @@
  * limitations under the License.
  */
 
-use crate::core::{byte::ByteReader, CharacterTableModifierFlags};
+use crate::core::tables::flagged;
+use crate::core::{byte::ByteReader, CharacterTableLinkFlags, CharacterTableModifierFlags};
 #[cfg(feature = "tagging")]
-use crate::core::{ByteIndex, Span, TableType, TagKind};
+use crate::core::{ByteIndex, Span, TagKind};
 use crate::core::{
     Character, CharacterTable, DeserializeEngine, DeserializeError, SerializeEngine,
-    SerializeError, Table, TagWriter,
+    SerializeError, Table, TableType, TagWriter,
 };
 
 pub(crate) mod deserialize;
@@ -27,10 +28,23 @@ pub(crate) use deserialize::*;
 pub(crate) mod serialize;
 pub(crate) use serialize::*;
 
+impl CharacterTable {
+    /// The pixmap tables this table links, or `None` unless
+    /// [`CharacterTableLinkFlags::LinkPixmapTables`] is set.
+    pub(crate) fn effective_links(&self) -> Option<&[u8]> {
+        flagged(
+            self.link_flags
+                .contains(CharacterTableLinkFlags::LinkPixmapTables),
+            self.pixmap_table_indexes.as_deref(),
+        )
+    }
+}
+
 impl Table for CharacterTable {
     fn deserialize<R: ByteReader, T: TagWriter>(
+        &mut self,
         engine: &mut DeserializeEngine<R, T>,
-    ) -> Result<Self, DeserializeError> {
+    ) -> Result<(), DeserializeError> {
         #[cfg(feature = "tagging")]
         let table_start = engine.bytes.byte_index();
         #[cfg(feature = "tagging")]
@@ -43,12 +57,11 @@ impl Table for CharacterTable {
             engine.bytes.byte_index(),
         );
 
-        let mut character_table = CharacterTable::default();
-        character_table.next_modifer_flags(engine);
-        character_table.next_configurations(engine);
-        character_table.next_table_links(engine)?;
+        self.next_modifer_flags(engine)?;
+        self.next_configurations(engine)?;
+        self.next_table_links(engine)?;
 
-        let character_count = engine.bytes.next();
+        let character_count = engine.bytes.next()?;
         #[cfg(feature = "tagging")]
         engine.tags.tag_byte(
             TagKind::CharacterTableCharacterCount {
@@ -59,66 +72,15 @@ impl Table for CharacterTable {
         );
 
         for index in 0..character_count {
-            #[cfg(feature = "tagging")]
-            {
-                engine.tagging_data.current_record_index = index;
-            }
+            engine.tagging_data.current_record_index = index;
+            engine.tagging_data.in_record = true;
             #[cfg(feature = "tagging")]
             let character_start = engine.bytes.byte_index();
 
             let mut character = Character::default();
-            if character_table
-                .modifier_flags
-                .contains(CharacterTableModifierFlags::UseAdvanceX)
-            {
-                character.advance_x = Some(engine.bytes.next());
-                #[cfg(feature = "tagging")]
-                engine.tags.tag_byte(
-                    TagKind::CharacterAdvanceX {
-                        table_index: engine.tagging_data.current_table_index,
-                        char_index: engine.tagging_data.current_record_index,
-                        value: character.advance_x.unwrap(),
-                    },
-                    engine.bytes.byte_index(),
-                );
-            }
-            if character_table
-                .modifier_flags
-                .contains(CharacterTableModifierFlags::UsePixmapIndex)
-            {
-                character.pixmap_index = Some(engine.bytes.next());
-                #[cfg(feature = "tagging")]
-                engine.tags.tag_byte(
-                    TagKind::CharacterPixmapIndex {
-                        table_index: engine.tagging_data.current_table_index,
-                        char_index: engine.tagging_data.current_record_index,
-                        value: character.pixmap_index.unwrap(),
-                    },
-                    engine.bytes.byte_index(),
-                );
-            }
-            if character_table
-                .modifier_flags
-                .contains(CharacterTableModifierFlags::UsePixmapTableIndex)
-            {
-                character.pixmap_table_index = Some(engine.bytes.next());
-                #[cfg(feature = "tagging")]
-                engine.tags.tag_byte(
-                    TagKind::CharacterPixmapTableIndex {
-                        table_index: engine.tagging_data.current_table_index,
-                        char_index: engine.tagging_data.current_record_index,
-                        value: character.pixmap_table_index.unwrap(),
-                    },
-                    engine.bytes.byte_index(),
-                );
-            }
-
-            next_code_points(
-                engine,
-                &mut character,
-                character_table.constant_code_point_count,
-            );
-            character_table.characters.push(character);
+            self.next_character_modifiers(engine, &mut character)?;
+            next_code_points(engine, &mut character, self.constant_code_point_count)?;
+            self.characters.push(character);
 
             #[cfg(feature = "tagging")]
             engine.tags.tag_span(
@@ -138,7 +100,7 @@ impl Table for CharacterTable {
             Span::new(table_start, engine.bytes.byte_index()),
         );
 
-        Ok(character_table)
+        Ok(())
     }
 
     fn serialize<T: TagWriter>(
@@ -150,7 +112,7 @@ impl Table for CharacterTable {
 
         self.push_table_identifier(engine);
         self.push_modifier_flags(engine);
-        self.push_configurations(engine);
+        self.push_configurations(engine)?;
         self.push_table_links(engine)?;
 
         // record length
@@ -170,10 +132,7 @@ impl Table for CharacterTable {
 
         // records
         for (index, character) in self.characters.iter().enumerate() {
-            #[cfg(feature = "tagging")]
-            {
-                engine.tagging_data.current_record_index = index as u8;
-            }
+            engine.tagging_data.current_record_index = index as u8;
             #[cfg(feature = "tagging")]
             let character_start = engine.bytes.byte_index();
 
@@ -181,13 +140,16 @@ impl Table for CharacterTable {
                 .modifier_flags
                 .contains(CharacterTableModifierFlags::UseAdvanceX)
             {
-                engine.bytes.push(character.advance_x.unwrap());
+                let advance_x = character.advance_x.ok_or_else(|| {
+                    engine.missing_record_field(TableType::Character, "advance_x")
+                })?;
+                engine.bytes.push(advance_x);
                 #[cfg(feature = "tagging")]
                 engine.tags.tag_byte(
                     TagKind::CharacterAdvanceX {
                         table_index: engine.tagging_data.current_table_index,
                         char_index: engine.tagging_data.current_record_index,
-                        value: character.advance_x.unwrap(),
+                        value: advance_x,
                     },
                     engine.bytes.byte_index(),
                 );
@@ -196,13 +158,16 @@ impl Table for CharacterTable {
                 .modifier_flags
                 .contains(CharacterTableModifierFlags::UsePixmapIndex)
             {
-                engine.bytes.push(character.pixmap_index.unwrap());
+                let pixmap_index = character.pixmap_index.ok_or_else(|| {
+                    engine.missing_record_field(TableType::Character, "pixmap_index")
+                })?;
+                engine.bytes.push(pixmap_index);
                 #[cfg(feature = "tagging")]
                 engine.tags.tag_byte(
                     TagKind::CharacterPixmapIndex {
                         table_index: engine.tagging_data.current_table_index,
                         char_index: engine.tagging_data.current_record_index,
-                        value: character.pixmap_index.unwrap(),
+                        value: pixmap_index,
                     },
                     engine.bytes.byte_index(),
                 );
@@ -211,13 +176,16 @@ impl Table for CharacterTable {
                 .modifier_flags
                 .contains(CharacterTableModifierFlags::UsePixmapTableIndex)
             {
-                engine.bytes.push(character.pixmap_table_index.unwrap());
+                let pixmap_table_index = character.pixmap_table_index.ok_or_else(|| {
+                    engine.missing_record_field(TableType::Character, "pixmap_table_index")
+                })?;
+                engine.bytes.push(pixmap_table_index);
                 #[cfg(feature = "tagging")]
                 engine.tags.tag_byte(
                     TagKind::CharacterPixmapTableIndex {
                         table_index: engine.tagging_data.current_table_index,
                         char_index: engine.tagging_data.current_record_index,
-                        value: character.pixmap_table_index.unwrap(),
+                        value: pixmap_table_index,
                     },
                     engine.bytes.byte_index(),
                 );
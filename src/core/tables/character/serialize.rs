@@ -14,10 +14,11 @@
  * limitations under the License.
  */
 
-#[cfg(feature = "tagging")]
-use crate::core::CharacterTableLinkFlags;
 use crate::core::CharacterTableModifierFlags;
-use crate::core::{CharacterTable, SerializeEngine, SerializeError, TableIdentifier, TagWriter};
+use crate::core::{
+    CharacterTable, CharacterTableConfigurationFlags, CharacterTableLinkFlags, SerializeEngine,
+    SerializeError, TableIdentifier, TableType, TagWriter,
+};
 use crate::{vec, String};
 
 #[cfg(feature = "tagging")]
@@ -34,13 +35,16 @@ impl CharacterTable {
         #[cfg(feature = "tagging")]
         engine.tags.tag_byte(
             TagKind::TableIdentifier {
-                table_type: crate::core::TableType::Character,
+                table_type: TableType::Character,
             },
             engine.bytes.byte_index(),
         );
     }
     pub(crate) fn push_modifier_flags<T: TagWriter>(&self, engine: &mut SerializeEngine<T>) {
-        engine.bytes.push(self.modifier_flags.bits());
+        engine.push_flags(
+            self.modifier_flags.bits(),
+            CharacterTableModifierFlags::all().bits(),
+        );
         #[cfg(feature = "tagging")]
         engine.tags.tag_bitflag(
             TagKind::CharacterTableModifierFlags {
@@ -70,11 +74,25 @@ impl CharacterTable {
             engine.bytes.byte_index(),
         );
     }
-    pub(crate) fn push_configurations<T: TagWriter>(&self, engine: &mut SerializeEngine<T>) {
+    pub(crate) fn push_configurations<T: TagWriter>(
+        &self,
+        engine: &mut SerializeEngine<T>,
+    ) -> Result<(), SerializeError> {
+        engine.check_table_field(
+            TableType::Character,
+            "constant_code_point_count",
+            self.configuration_flags
+                .contains(CharacterTableConfigurationFlags::ConstantCodePointCount),
+            self.constant_code_point_count.is_some(),
+        )?;
+
         #[cfg(feature = "tagging")]
         let configurations_start = engine.bytes.byte_index();
 
-        engine.bytes.push(self.configuration_flags.bits()); // Configuration flags byte
+        engine.push_flags(
+            self.configuration_flags.bits(),
+            CharacterTableConfigurationFlags::all().bits(),
+        ); // Configuration flags byte
         #[cfg(feature = "tagging")]
         engine.tags.tag_bitflag(
             TagKind::CharacterTableConfigurationFlags {
@@ -118,6 +136,8 @@ impl CharacterTable {
                 Span::new(configurations_start, engine.bytes.byte_index()),
             );
         }
+
+        Ok(())
     }
 
     pub(crate) fn push_table_links<T: TagWriter>(
@@ -127,8 +147,19 @@ impl CharacterTable {
         #[cfg(feature = "tagging")]
         let links_start = engine.bytes.byte_index();
 
+        engine.check_table_field(
+            TableType::Character,
+            "pixmap_table_indexes",
+            self.link_flags
+                .contains(CharacterTableLinkFlags::LinkPixmapTables),
+            self.pixmap_table_indexes.is_some(),
+        )?;
+
         // Table relations
-        engine.bytes.push(self.link_flags.bits());
+        engine.push_flags(
+            self.link_flags.bits(),
+            CharacterTableLinkFlags::all().bits(),
+        );
         #[cfg(feature = "tagging")]
         engine.tags.tag_bitflag(
             TagKind::CharacterTableLinkFlags {
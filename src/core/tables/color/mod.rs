@@ -15,22 +15,25 @@
  */
 
 use crate::core::byte::ByteReader;
+use crate::core::tables::flagged;
 use crate::core::{
     Color, ColorTable, ColorTableConfigurationFlags, ColorTableModifierFlags, ColorType,
     DeserializeEngine, DeserializeError, SerializeEngine, SerializeError, Table, TableIdentifier,
-    TagWriter,
+    TableType, TagWriter,
 };
 use crate::{vec, Vec};
 
 #[cfg(feature = "tagging")]
-use crate::core::{ByteIndex, Span, TableType, TagKind};
+use crate::core::{ByteIndex, Span, TagKind};
 
 impl ColorTable {
     pub(crate) fn next_modifer_flags<R: ByteReader, T: TagWriter>(
         &mut self,
         engine: &mut DeserializeEngine<R, T>,
-    ) {
-        self.modifier_flags = ColorTableModifierFlags::from_bits_retain(engine.bytes.next());
+    ) -> Result<(), DeserializeError> {
+        self.modifier_flags = ColorTableModifierFlags::from_bits_retain(
+            engine.next_flags(ColorTableModifierFlags::all().bits())?,
+        );
         #[cfg(feature = "tagging")]
         engine.tags.tag_bitflag(
             TagKind::ColorTableModifierFlags {
@@ -45,16 +48,19 @@ impl ColorTable {
             }],
             engine.bytes.byte_index(),
         );
+
+        Ok(())
     }
     pub(crate) fn next_configurations<R: ByteReader, T: TagWriter>(
         &mut self,
         engine: &mut DeserializeEngine<R, T>,
-    ) {
+    ) -> Result<(), DeserializeError> {
         #[cfg(feature = "tagging")]
         let configurations_start = engine.bytes.byte_index();
 
-        self.configuration_flags =
-            ColorTableConfigurationFlags::from_bits_retain(engine.bytes.next());
+        self.configuration_flags = ColorTableConfigurationFlags::from_bits_retain(
+            engine.next_flags(ColorTableConfigurationFlags::all().bits())?,
+        );
         let use_constant_alpha = self
             .configuration_flags
             .contains(ColorTableConfigurationFlags::ConstantAlpha);
@@ -74,7 +80,7 @@ impl ColorTable {
         #[cfg(feature = "tagging")]
         let configuration_values_start = engine.bytes.byte_index();
         if use_constant_alpha {
-            self.constant_alpha = Some(engine.bytes.next());
+            self.constant_alpha = Some(engine.bytes.next()?);
             #[cfg(feature = "tagging")]
             engine.tags.tag_byte(
                 TagKind::ColorTableConstantAlpha {
@@ -100,15 +106,17 @@ impl ColorTable {
                 Span::new(configurations_start, engine.bytes.byte_index()),
             );
         }
+
+        Ok(())
     }
     pub(crate) fn next_table_links<R: ByteReader, T: TagWriter>(
         &mut self,
         engine: &mut DeserializeEngine<R, T>,
-    ) {
+    ) -> Result<(), DeserializeError> {
         #[cfg(feature = "tagging")]
         let links_start = engine.bytes.byte_index();
 
-        let _link_flags = engine.bytes.next(); // will need to be updated later to use bitflags
+        let _link_flags = engine.next_flags(0)?; // will need to be updated later to use bitflags
         #[cfg(feature = "tagging")]
         engine.tags.tag_bitflag(
             TagKind::ColorTableLinkFlags {
@@ -127,13 +135,33 @@ impl ColorTable {
                 Span::new(links_start, engine.bytes.byte_index()),
             );
         }
+
+        Ok(())
+    }
+}
+
+impl ColorTable {
+    /// The color type of `color`, or `None` unless [`ColorTableModifierFlags::UseColorType`]
+    /// is set.
+    pub(crate) fn color_type(&self, color: &Color) -> Option<ColorType> {
+        flagged(
+            self.modifier_flags
+                .contains(ColorTableModifierFlags::UseColorType),
+            color.color_type,
+        )
+    }
+
+    /// The alpha of `color`: this table's constant alpha, or else the color's custom one.
+    pub(crate) fn alpha(&self, color: &Color) -> Option<u8> {
+        self.constant_alpha.or(color.custom_alpha)
     }
 }
 
 impl Table for ColorTable {
     fn deserialize<R: ByteReader, T: TagWriter>(
+        &mut self,
         engine: &mut DeserializeEngine<R, T>,
-    ) -> Result<Self, DeserializeError> {
+    ) -> Result<(), DeserializeError> {
         #[cfg(feature = "tagging")]
         let table_start = engine.bytes.byte_index();
         #[cfg(feature = "tagging")]
@@ -146,12 +174,11 @@ impl Table for ColorTable {
             engine.bytes.byte_index(),
         );
 
-        let mut color_table = ColorTable::default();
-        color_table.next_modifer_flags(engine);
-        color_table.next_configurations(engine);
-        color_table.next_table_links(engine);
+        self.next_modifer_flags(engine)?;
+        self.next_configurations(engine)?;
+        self.next_table_links(engine)?;
 
-        let color_count = engine.bytes.next();
+        let color_count = engine.bytes.next()?;
         #[cfg(feature = "tagging")]
         engine.tags.tag_byte(
             TagKind::ColorTableColorCount {
@@ -161,19 +188,17 @@ impl Table for ColorTable {
             engine.bytes.byte_index(),
         );
         for index in 0..color_count {
-            #[cfg(feature = "tagging")]
-            {
-                engine.tagging_data.current_record_index = index;
-            }
+            engine.tagging_data.current_record_index = index;
+            engine.tagging_data.in_record = true;
             #[cfg(feature = "tagging")]
             let color_start = engine.bytes.byte_index();
 
             let mut color = Color::default();
-            if color_table
+            if self
                 .modifier_flags
                 .contains(ColorTableModifierFlags::UseColorType)
             {
-                color.color_type = Some(ColorType::try_from(engine.bytes.next())?);
+                color.color_type = Some(engine.next_decoded(ColorType::try_from)?);
                 #[cfg(feature = "tagging")]
                 engine.tags.tag_byte(
                     TagKind::ColorColorType {
@@ -185,8 +210,8 @@ impl Table for ColorTable {
                 );
             }
 
-            if color_table.constant_alpha.is_none() {
-                color.custom_alpha = Some(engine.bytes.next());
+            if self.constant_alpha.is_none() {
+                color.custom_alpha = Some(engine.bytes.next()?);
                 #[cfg(feature = "tagging")]
                 engine.tags.tag_byte(
                     TagKind::ColorCustomAlpha {
@@ -197,7 +222,7 @@ impl Table for ColorTable {
                     engine.bytes.byte_index(),
                 );
             }
-            color.red = engine.bytes.next();
+            color.red = engine.bytes.next()?;
             #[cfg(feature = "tagging")]
             engine.tags.tag_byte(
                 TagKind::ColorRed {
@@ -207,7 +232,7 @@ impl Table for ColorTable {
                 },
                 engine.bytes.byte_index(),
             );
-            color.green = engine.bytes.next();
+            color.green = engine.bytes.next()?;
             #[cfg(feature = "tagging")]
             engine.tags.tag_byte(
                 TagKind::ColorGreen {
@@ -217,7 +242,7 @@ impl Table for ColorTable {
                 },
                 engine.bytes.byte_index(),
             );
-            color.blue = engine.bytes.next();
+            color.blue = engine.bytes.next()?;
             #[cfg(feature = "tagging")]
             engine.tags.tag_byte(
                 TagKind::ColorBlue {
@@ -228,7 +253,7 @@ impl Table for ColorTable {
                 engine.bytes.byte_index(),
             );
 
-            color_table.colors.push(color);
+            self.colors.push(color);
 
             #[cfg(feature = "tagging")]
             engine.tags.tag_span(
@@ -247,7 +272,7 @@ impl Table for ColorTable {
             },
             Span::new(table_start, engine.bytes.byte_index()),
         );
-        Ok(color_table)
+        Ok(())
     }
 
     fn serialize<T: TagWriter>(
@@ -259,7 +284,7 @@ impl Table for ColorTable {
 
         self.push_table_identifier(engine);
         self.push_modifier_flags(engine);
-        self.push_configurations(engine);
+        self.push_configurations(engine)?;
         self.push_table_links(engine);
 
         if self.colors.len() > 255 {
@@ -276,10 +301,7 @@ impl Table for ColorTable {
         );
 
         for (index, color) in self.colors.iter().enumerate() {
-            #[cfg(feature = "tagging")]
-            {
-                engine.tagging_data.current_record_index = index as u8;
-            }
+            engine.tagging_data.current_record_index = index as u8;
             #[cfg(feature = "tagging")]
             let color_start = engine.bytes.byte_index();
 
@@ -287,26 +309,32 @@ impl Table for ColorTable {
                 .modifier_flags
                 .contains(ColorTableModifierFlags::UseColorType)
             {
-                engine.bytes.push(color.color_type.unwrap() as u8);
+                let color_type = color
+                    .color_type
+                    .ok_or_else(|| engine.missing_record_field(TableType::Color, "color_type"))?;
+                engine.bytes.push(color_type as u8);
                 #[cfg(feature = "tagging")]
                 engine.tags.tag_byte(
                     TagKind::ColorColorType {
                         table_index: engine.tagging_data.current_table_index,
                         color_index: engine.tagging_data.current_record_index,
-                        value: color.color_type.unwrap(),
+                        value: color_type,
                     },
                     engine.bytes.byte_index(),
                 );
             }
 
             if self.constant_alpha.is_none() {
-                engine.bytes.push(color.custom_alpha.unwrap());
+                let custom_alpha = color
+                    .custom_alpha
+                    .ok_or_else(|| engine.missing_record_field(TableType::Color, "custom_alpha"))?;
+                engine.bytes.push(custom_alpha);
                 #[cfg(feature = "tagging")]
                 engine.tags.tag_byte(
                     TagKind::ColorCustomAlpha {
                         table_index: engine.tagging_data.current_table_index,
                         color_index: engine.tagging_data.current_record_index,
-                        value: color.custom_alpha.unwrap(),
+                        value: custom_alpha,
                     },
                     engine.bytes.byte_index(),
                 );
@@ -376,7 +404,10 @@ impl ColorTable {
         );
     }
     pub(crate) fn push_modifier_flags<T: TagWriter>(&self, engine: &mut SerializeEngine<T>) {
-        engine.bytes.push(self.modifier_flags.bits());
+        engine.push_flags(
+            self.modifier_flags.bits(),
+            ColorTableModifierFlags::all().bits(),
+        );
         #[cfg(feature = "tagging")]
         engine.tags.tag_bitflag(
             TagKind::ColorTableModifierFlags {
@@ -392,11 +423,25 @@ impl ColorTable {
             engine.bytes.byte_index(),
         );
     }
-    pub(crate) fn push_configurations<T: TagWriter>(&self, engine: &mut SerializeEngine<T>) {
+    pub(crate) fn push_configurations<T: TagWriter>(
+        &self,
+        engine: &mut SerializeEngine<T>,
+    ) -> Result<(), SerializeError> {
+        engine.check_table_field(
+            TableType::Color,
+            "constant_alpha",
+            self.configuration_flags
+                .contains(ColorTableConfigurationFlags::ConstantAlpha),
+            self.constant_alpha.is_some(),
+        )?;
+
         #[cfg(feature = "tagging")]
         let configurations_start = engine.bytes.byte_index();
 
-        engine.bytes.push(self.configuration_flags.bits()); // configuration flags
+        engine.push_flags(
+            self.configuration_flags.bits(),
+            ColorTableConfigurationFlags::all().bits(),
+        ); // configuration flags
         #[cfg(feature = "tagging")]
         engine.tags.tag_bitflag(
             TagKind::ColorTableConfigurationFlags {
@@ -440,6 +485,8 @@ impl ColorTable {
                 Span::new(configurations_start, engine.bytes.byte_index()),
             );
         }
+
+        Ok(())
     }
     pub(crate) fn push_table_links<T: TagWriter>(&self, engine: &mut SerializeEngine<T>) {
         #[cfg(feature = "tagging")]
@@ -16,7 +16,8 @@
 
 use crate::core::byte::ByteReader;
 use crate::core::{
-    DeserializeEngine, DeserializeError, Font, FontTable, FontTableLinkFlags, FontType, TagWriter,
+    DeserializeEngine, DeserializeError, Font, FontTable, FontTableLinkFlags, FontType, Limit,
+    TagWriter,
 };
 use crate::{vec, String, Vec};
 
@@ -27,8 +28,8 @@ impl FontTable {
     pub(crate) fn next_modifer_flags<R: ByteReader, T: TagWriter>(
         &mut self,
         engine: &mut DeserializeEngine<R, T>,
-    ) {
-        engine.bytes.next();
+    ) -> Result<(), DeserializeError> {
+        engine.next_flags(0)?;
         #[cfg(feature = "tagging")]
         engine.tags.tag_bitflag(
             TagKind::FontTableModifierFlags {
@@ -37,12 +38,14 @@ impl FontTable {
             vec![],
             engine.bytes.byte_index(),
         );
+
+        Ok(())
     }
     pub(crate) fn next_configurations<R: ByteReader, T: TagWriter>(
         &mut self,
         engine: &mut DeserializeEngine<R, T>,
-    ) {
-        engine.bytes.next();
+    ) -> Result<(), DeserializeError> {
+        engine.next_flags(0)?;
         #[cfg(feature = "tagging")]
         engine.tags.tag_bitflag(
             TagKind::FontTableConfigurationFlags {
@@ -51,6 +54,8 @@ impl FontTable {
             vec![],
             engine.bytes.byte_index(),
         );
+
+        Ok(())
     }
     pub(crate) fn next_table_links<R: ByteReader, T: TagWriter>(
         &mut self,
@@ -59,7 +64,9 @@ impl FontTable {
         #[cfg(feature = "tagging")]
         let links_start = engine.bytes.byte_index();
 
-        self.link_flags = FontTableLinkFlags::from_bits_retain(engine.bytes.next());
+        self.link_flags = FontTableLinkFlags::from_bits_retain(
+            engine.next_flags(FontTableLinkFlags::all().bits())?,
+        );
         let link_character_tables = self
             .link_flags
             .contains(FontTableLinkFlags::LinkCharacterTables);
@@ -80,7 +87,7 @@ impl FontTable {
             #[cfg(feature = "tagging")]
             let character_tables_start = engine.bytes.byte_index();
 
-            let character_tables_length = engine.bytes.next();
+            let character_tables_length = engine.bytes.next()?;
             #[cfg(feature = "tagging")]
             engine.tags.tag_byte(
                 TagKind::FontTableCharacterTableIndexesLength {
@@ -95,7 +102,7 @@ impl FontTable {
 
             let mut character_table_indexes = Vec::new();
             for _ in 0..character_tables_length {
-                let link_index = engine.bytes.next();
+                let link_index = engine.bytes.next()?;
                 character_table_indexes.push(link_index);
                 #[cfg(feature = "tagging")]
                 engine.tags.tag_byte(
@@ -140,55 +147,63 @@ impl FontTable {
 
 pub(crate) fn next_string<R: ByteReader, T: TagWriter>(
     engine: &mut DeserializeEngine<R, T>,
-) -> String {
+) -> Result<String, DeserializeError> {
     let mut string = String::new();
     let mut end_cluster = false;
 
     while !end_cluster {
-        let utf81 = engine.bytes.next();
+        let invalid = DeserializeError::InvalidUtf8 {
+            table: engine.tagging_data.current_table_index,
+            record: engine.tagging_data.current_record_index,
+        };
+        let character_start = engine.bytes.byte_index();
+        let utf81 = engine.bytes.next()?;
         let mut utf8_bytes: [u8; 4] = [0, 0, 0, 0];
 
         if utf81 >> 7 == 0b00000000 {
             utf8_bytes[0] = utf81;
         } else if utf81 >> 5 == 0b00000110 {
             utf8_bytes[0] = utf81;
-            utf8_bytes[1] = engine.bytes.next();
+            utf8_bytes[1] = engine.bytes.next()?;
         } else if utf81 >> 4 == 0b00001110 {
             utf8_bytes[0] = utf81;
-            utf8_bytes[1] = engine.bytes.next();
-            utf8_bytes[2] = engine.bytes.next();
+            utf8_bytes[1] = engine.bytes.next()?;
+            utf8_bytes[2] = engine.bytes.next()?;
         } else if utf81 >> 3 == 0b00011110 {
             utf8_bytes[0] = utf81;
-            utf8_bytes[1] = engine.bytes.next();
-            utf8_bytes[2] = engine.bytes.next();
-            utf8_bytes[3] = engine.bytes.next();
+            utf8_bytes[1] = engine.bytes.next()?;
+            utf8_bytes[2] = engine.bytes.next()?;
+            utf8_bytes[3] = engine.bytes.next()?;
+        } else {
+            return Err(engine.error_at(character_start, invalid));
         }
 
-        string.push(
-            String::from_utf8(utf8_bytes.to_vec())
-                .unwrap()
-                .chars()
-                .next()
-                .unwrap(),
-        );
+        let character = core::str::from_utf8(&utf8_bytes)
+            .ok()
+            .and_then(|string| string.chars().next())
+            .ok_or_else(|| engine.error_at(character_start, invalid))?;
+        string.push(character);
+        engine
+            .check_limit(Limit::StringLength, string.len())
+            .map_err(|error| engine.error_at(character_start, error))?;
 
-        if engine.bytes.get() == 0 {
+        if engine.bytes.get()? == 0 {
             end_cluster = true;
-            engine.bytes.next();
+            engine.bytes.next()?;
         }
     }
 
-    string
+    Ok(string)
 }
 
 pub(crate) fn next_name<R: ByteReader, T: TagWriter>(
     engine: &mut DeserializeEngine<R, T>,
     font: &mut Font,
-) {
+) -> Result<(), DeserializeError> {
     #[cfg(feature = "tagging")]
     let start = engine.bytes.byte_index();
 
-    font.name = next_string(engine);
+    font.name = next_string(engine)?;
 
     #[cfg(feature = "tagging")]
     engine.tags.tag_span(
@@ -199,16 +214,18 @@ pub(crate) fn next_name<R: ByteReader, T: TagWriter>(
         },
         Span::new(start, engine.bytes.byte_index()),
     );
+
+    Ok(())
 }
 
 pub(crate) fn next_author<R: ByteReader, T: TagWriter>(
     engine: &mut DeserializeEngine<R, T>,
     font: &mut Font,
-) {
+) -> Result<(), DeserializeError> {
     #[cfg(feature = "tagging")]
     let start = engine.bytes.byte_index();
 
-    font.author = next_string(engine);
+    font.author = next_string(engine)?;
 
     #[cfg(feature = "tagging")]
     engine.tags.tag_span(
@@ -219,13 +236,15 @@ pub(crate) fn next_author<R: ByteReader, T: TagWriter>(
         },
         Span::new(start, engine.bytes.byte_index()),
     );
+
+    Ok(())
 }
 
 pub(crate) fn next_version<R: ByteReader, T: TagWriter>(
     engine: &mut DeserializeEngine<R, T>,
     font: &mut Font,
-) {
-    let version = engine.bytes.next();
+) -> Result<(), DeserializeError> {
+    let version = engine.bytes.next()?;
     #[cfg(feature = "tagging")]
     engine.tags.tag_byte(
         TagKind::FontVersion {
@@ -236,14 +255,15 @@ pub(crate) fn next_version<R: ByteReader, T: TagWriter>(
         engine.bytes.byte_index(),
     );
     font.version = version;
+
+    Ok(())
 }
 
 pub(crate) fn next_font_type<R: ByteReader, T: TagWriter>(
     engine: &mut DeserializeEngine<R, T>,
     font: &mut Font,
 ) -> Result<(), DeserializeError> {
-    let font_type = engine.bytes.next();
-    let font_type = FontType::try_from(font_type)?;
+    let font_type = engine.next_decoded(FontType::try_from)?;
     #[cfg(feature = "tagging")]
     engine.tags.tag_byte(
         TagKind::FontFontType {
@@ -261,11 +281,11 @@ pub(crate) fn next_font_type<R: ByteReader, T: TagWriter>(
 pub(crate) fn next_linked_character_table_indexes<R: ByteReader, T: TagWriter>(
     engine: &mut DeserializeEngine<R, T>,
     font: &mut Font,
-) {
+) -> Result<(), DeserializeError> {
     #[cfg(feature = "tagging")]
     let start = engine.bytes.byte_index();
 
-    let linked_character_table_indexes_length = engine.bytes.next();
+    let linked_character_table_indexes_length = engine.bytes.next()?;
     #[cfg(feature = "tagging")]
     engine.tags.tag_byte(
         TagKind::FontLinkedCharacterTableIndexesLength {
@@ -278,7 +298,7 @@ pub(crate) fn next_linked_character_table_indexes<R: ByteReader, T: TagWriter>(
 
     let mut linked_character_table_indexes = Vec::new();
     for _ in 0..linked_character_table_indexes_length {
-        let character_table_index = engine.bytes.next();
+        let character_table_index = engine.bytes.next()?;
         linked_character_table_indexes.push(character_table_index);
         #[cfg(feature = "tagging")]
         engine.tags.tag_byte(
@@ -302,4 +322,6 @@ pub(crate) fn next_linked_character_table_indexes<R: ByteReader, T: TagWriter>(
         },
         Span::new(start, engine.bytes.byte_index()),
     );
+
+    Ok(())
 }
@@ -14,7 +14,8 @@
  * limitations under the License.
  */
 
-use crate::core::{byte::ByteReader, Font};
+use crate::core::tables::flagged;
+use crate::core::{byte::ByteReader, Font, FontTableLinkFlags};
 #[cfg(feature = "tagging")]
 use crate::core::{ByteIndex, Span, TableType, TagKind};
 use crate::core::{
@@ -27,10 +28,23 @@ pub(crate) use deserialize::*;
 pub(crate) mod serialize;
 pub(crate) use serialize::*;
 
+impl FontTable {
+    /// The character tables this table links, or `None` unless
+    /// [`FontTableLinkFlags::LinkCharacterTables`] is set.
+    pub(crate) fn effective_links(&self) -> Option<&[u8]> {
+        flagged(
+            self.link_flags
+                .contains(FontTableLinkFlags::LinkCharacterTables),
+            self.character_table_indexes.as_deref(),
+        )
+    }
+}
+
 impl Table for FontTable {
     fn deserialize<R: ByteReader, T: TagWriter>(
+        &mut self,
         engine: &mut DeserializeEngine<R, T>,
-    ) -> Result<Self, DeserializeError> {
+    ) -> Result<(), DeserializeError> {
         #[cfg(feature = "tagging")]
         let table_start = engine.bytes.byte_index();
         #[cfg(feature = "tagging")]
@@ -43,12 +57,11 @@ impl Table for FontTable {
             engine.bytes.byte_index(),
         );
 
-        let mut font_table = FontTable::default();
-        font_table.next_modifer_flags(engine);
-        font_table.next_configurations(engine);
-        font_table.next_table_links(engine)?;
+        self.next_modifer_flags(engine)?;
+        self.next_configurations(engine)?;
+        self.next_table_links(engine)?;
 
-        let font_count = engine.bytes.next();
+        let font_count = engine.bytes.next()?;
         #[cfg(feature = "tagging")]
         engine.tags.tag_byte(
             TagKind::FontTableFontCount {
@@ -59,21 +72,19 @@ impl Table for FontTable {
         );
 
         for index in 0..font_count {
-            #[cfg(feature = "tagging")]
-            {
-                engine.tagging_data.current_record_index = index;
-            }
+            engine.tagging_data.current_record_index = index;
+            engine.tagging_data.in_record = true;
             #[cfg(feature = "tagging")]
             let font_start = engine.bytes.byte_index();
 
             let mut font = Font::default();
 
-            next_name(engine, &mut font);
-            next_author(engine, &mut font);
-            next_version(engine, &mut font);
+            next_name(engine, &mut font)?;
+            next_author(engine, &mut font)?;
+            next_version(engine, &mut font)?;
             next_font_type(engine, &mut font)?;
-            next_linked_character_table_indexes(engine, &mut font);
-            font_table.fonts.push(font);
+            next_linked_character_table_indexes(engine, &mut font)?;
+            self.fonts.push(font);
 
             #[cfg(feature = "tagging")]
             engine.tags.tag_span(
@@ -93,7 +104,7 @@ impl Table for FontTable {
             Span::new(table_start, engine.bytes.byte_index()),
         );
 
-        Ok(font_table)
+        Ok(())
     }
 
     fn serialize<T: TagWriter>(
@@ -125,10 +136,7 @@ impl Table for FontTable {
 
         // records
         for (index, font) in self.fonts.iter().enumerate() {
-            #[cfg(feature = "tagging")]
-            {
-                engine.tagging_data.current_record_index = index as u8;
-            }
+            engine.tagging_data.current_record_index = index as u8;
             #[cfg(feature = "tagging")]
             let font_start = engine.bytes.byte_index();
 
@@ -14,10 +14,9 @@
  * limitations under the License.
  */
 
-#[cfg(feature = "tagging")]
-use crate::core::FontTableLinkFlags;
 use crate::core::{
-    FontTable, FontType, SerializeEngine, SerializeError, TableIdentifier, TagWriter,
+    FontTable, FontTableLinkFlags, FontType, SerializeEngine, SerializeError, TableIdentifier,
+    TableType, TagWriter,
 };
 use crate::{vec, Vec};
 
@@ -33,7 +32,7 @@ impl FontTable {
         #[cfg(feature = "tagging")]
         engine.tags.tag_byte(
             TagKind::TableIdentifier {
-                table_type: crate::core::TableType::Font,
+                table_type: TableType::Font,
             },
             engine.bytes.byte_index(),
         );
@@ -68,8 +67,16 @@ impl FontTable {
         #[cfg(feature = "tagging")]
         let links_start = engine.bytes.byte_index();
 
+        engine.check_table_field(
+            TableType::Font,
+            "character_table_indexes",
+            self.link_flags
+                .contains(FontTableLinkFlags::LinkCharacterTables),
+            self.character_table_indexes.is_some(),
+        )?;
+
         // Table relations
-        engine.bytes.push(self.link_flags.bits());
+        engine.push_flags(self.link_flags.bits(), FontTableLinkFlags::all().bits());
         #[cfg(feature = "tagging")]
         engine.tags.tag_bitflag(
             TagKind::FontTableLinkFlags {
@@ -18,3 +18,8 @@ pub(crate) mod character;
 pub(crate) mod color;
 pub(crate) mod font;
 pub(crate) mod pixmap;
+
+/// Returns `value` only if `flag_set`, as a field without its flag is never written.
+pub(crate) fn flagged<T>(flag_set: bool, value: Option<T>) -> Option<T> {
+    value.filter(|_| flag_set)
+}
@@ -16,8 +16,8 @@
 
 use crate::core::byte::ByteReader;
 use crate::core::{
-    DeserializeEngine, Pixmap, PixmapTable, PixmapTableConfigurationFlags, PixmapTableLinkFlags,
-    TagWriter,
+    DeserializeEngine, DeserializeError, Limit, Pixmap, PixmapTable, PixmapTableConfigurationFlags,
+    PixmapTableLinkFlags, TagWriter,
 };
 use crate::{vec, Vec};
 
@@ -31,8 +31,8 @@ impl PixmapTable {
     pub(crate) fn next_modifier_flags<R: ByteReader, T: TagWriter>(
         &mut self,
         engine: &mut DeserializeEngine<R, T>,
-    ) {
-        let _modifier_flags = engine.bytes.next();
+    ) -> Result<(), DeserializeError> {
+        let _modifier_flags = engine.next_flags(0)?;
         #[cfg(feature = "tagging")]
         engine.tags.tag_bitflag(
             TagKind::PixmapTableModifierFlags {
@@ -41,16 +41,19 @@ impl PixmapTable {
             Vec::new(),
             engine.bytes.byte_index(),
         );
+
+        Ok(())
     }
     pub(crate) fn next_configurations<R: ByteReader, T: TagWriter>(
         &mut self,
         engine: &mut DeserializeEngine<R, T>,
-    ) {
+    ) -> Result<(), DeserializeError> {
         #[cfg(feature = "tagging")]
         let configurations_start = engine.bytes.byte_index();
 
-        self.configuration_flags =
-            PixmapTableConfigurationFlags::from_bits_retain(engine.bytes.next());
+        self.configuration_flags = PixmapTableConfigurationFlags::from_bits_retain(
+            engine.next_flags(PixmapTableConfigurationFlags::all().bits())?,
+        );
         let use_constant_width = self
             .configuration_flags
             .contains(PixmapTableConfigurationFlags::ConstantWidth);
@@ -86,7 +89,7 @@ impl PixmapTable {
         #[cfg(feature = "tagging")]
         let configuration_values_start = engine.bytes.byte_index();
         if use_constant_width {
-            self.constant_width = Some(engine.bytes.next());
+            self.constant_width = Some(engine.bytes.next()?);
             #[cfg(feature = "tagging")]
             engine.tags.tag_byte(
                 TagKind::PixmapTableConstantWidth {
@@ -97,7 +100,7 @@ impl PixmapTable {
             );
         }
         if use_constant_height {
-            self.constant_height = Some(engine.bytes.next());
+            self.constant_height = Some(engine.bytes.next()?);
             #[cfg(feature = "tagging")]
             engine.tags.tag_byte(
                 TagKind::PixmapTableConstantHeight {
@@ -108,7 +111,7 @@ impl PixmapTable {
             );
         }
         if use_constant_bits_per_pixel {
-            self.constant_bits_per_pixel = Some(engine.bytes.next());
+            self.constant_bits_per_pixel = Some(engine.bytes.next()?);
             #[cfg(feature = "tagging")]
             engine.tags.tag_byte(
                 TagKind::PixmapTableConstantBitsPerPixel {
@@ -134,16 +137,20 @@ impl PixmapTable {
             },
             Span::new(configurations_start, engine.bytes.byte_index()),
         );
+
+        Ok(())
     }
 
     pub(crate) fn next_table_links<R: ByteReader, T: TagWriter>(
         &mut self,
         engine: &mut DeserializeEngine<R, T>,
-    ) {
+    ) -> Result<(), DeserializeError> {
         #[cfg(feature = "tagging")]
         let links_start = engine.bytes.byte_index();
 
-        self.link_flags = PixmapTableLinkFlags::from_bits_retain(engine.bytes.next());
+        self.link_flags = PixmapTableLinkFlags::from_bits_retain(
+            engine.next_flags(PixmapTableLinkFlags::all().bits())?,
+        );
         let link_color_tables = self
             .link_flags
             .contains(PixmapTableLinkFlags::LinkColorTables);
@@ -164,7 +171,7 @@ impl PixmapTable {
             #[cfg(feature = "tagging")]
             let color_tables_start = engine.bytes.byte_index();
 
-            let color_tables_length = engine.bytes.next();
+            let color_tables_length = engine.bytes.next()?;
             #[cfg(feature = "tagging")]
             engine.tags.tag_byte(
                 TagKind::PixmapTableColorTableIndexesLength {
@@ -179,7 +186,7 @@ impl PixmapTable {
 
             let mut color_table_indexes = Vec::new();
             for _ in 0..color_tables_length {
-                let link_index = engine.bytes.next();
+                let link_index = engine.bytes.next()?;
                 color_table_indexes.push(link_index);
                 #[cfg(feature = "tagging")]
                 engine.tags.tag_byte(
@@ -217,6 +224,8 @@ impl PixmapTable {
             },
             Span::new(links_start, engine.bytes.byte_index()),
         );
+
+        Ok(())
     }
 }
 
@@ -224,9 +233,9 @@ pub(crate) fn next_width<R: ByteReader, T: TagWriter>(
     engine: &mut DeserializeEngine<R, T>,
     pixmap: &mut Pixmap,
     constant_width: Option<u8>,
-) {
+) -> Result<(), DeserializeError> {
     if constant_width.is_none() {
-        pixmap.custom_width = Some(engine.bytes.next());
+        pixmap.custom_width = Some(engine.bytes.next()?);
         #[cfg(feature = "tagging")]
         engine.tags.tag_byte(
             TagKind::PixmapCustomWidth {
@@ -240,15 +249,17 @@ pub(crate) fn next_width<R: ByteReader, T: TagWriter>(
         #[cfg(feature = "log")]
         info!("Identified custom width: {:?}", pixmap.custom_width);
     }
+
+    Ok(())
 }
 
 pub(crate) fn next_height<R: ByteReader, T: TagWriter>(
     engine: &mut DeserializeEngine<R, T>,
     pixmap: &mut Pixmap,
     constant_height: Option<u8>,
-) {
+) -> Result<(), DeserializeError> {
     if constant_height.is_none() {
-        pixmap.custom_height = Some(engine.bytes.next());
+        pixmap.custom_height = Some(engine.bytes.next()?);
         #[cfg(feature = "tagging")]
         engine.tags.tag_byte(
             TagKind::PixmapCustomHeight {
@@ -262,15 +273,17 @@ pub(crate) fn next_height<R: ByteReader, T: TagWriter>(
         #[cfg(feature = "log")]
         info!("Identified custom height: {:?}", pixmap.custom_height);
     }
+
+    Ok(())
 }
 
 pub(crate) fn next_bits_per_pixel<R: ByteReader, T: TagWriter>(
     engine: &mut DeserializeEngine<R, T>,
     pixmap: &mut Pixmap,
     constant_bits_per_pixel: Option<u8>,
-) {
+) -> Result<(), DeserializeError> {
     if constant_bits_per_pixel.is_none() {
-        pixmap.custom_bits_per_pixel = Some(engine.bytes.next());
+        pixmap.custom_bits_per_pixel = Some(engine.bytes.next()?);
         #[cfg(feature = "tagging")]
         engine.tags.tag_byte(
             TagKind::PixmapCustomBitsPerPixel {
@@ -287,6 +300,8 @@ pub(crate) fn next_bits_per_pixel<R: ByteReader, T: TagWriter>(
             pixmap.custom_bits_per_pixel
         );
     }
+
+    Ok(())
 }
 
 pub(crate) fn next_pixmap<R: ByteReader, T: TagWriter>(
@@ -295,7 +310,7 @@ pub(crate) fn next_pixmap<R: ByteReader, T: TagWriter>(
     constant_width: Option<u8>,
     constant_height: Option<u8>,
     constant_bits_per_pixel: Option<u8>,
-) {
+) -> Result<(), DeserializeError> {
     #[cfg(feature = "tagging")]
     let pixmap_start = engine.bytes.byte_index();
 
@@ -305,21 +320,24 @@ pub(crate) fn next_pixmap<R: ByteReader, T: TagWriter>(
     let width = constant_width.or(pixmap.custom_width).unwrap();
     let height = constant_height.or(pixmap.custom_height).unwrap();
 
-    let pixels_used = width as u16 * height as u16;
-    let total_bits = pixels_used * bits_per_pixel as u16;
-    let complete_bytes_used = (total_bits / 8) as usize;
+    let pixels_used = width as usize * height as usize;
+    let total_bits = pixels_used * bits_per_pixel as usize;
+    let complete_bytes_used = total_bits / 8;
+
+    engine.pixmap_bytes += total_bits.div_ceil(8);
+    engine.check_limit(Limit::PixmapBytes, engine.pixmap_bytes)?;
 
     for _ in 0..complete_bytes_used {
-        pixmap.data.push(engine.bytes.next());
+        pixmap.data.push(engine.bytes.next()?);
     }
 
     let remainder_bits = (total_bits % 8) as u8;
     if !engine.layout.compact && remainder_bits > 0 {
-        pixmap.data.push(engine.bytes.next());
+        pixmap.data.push(engine.bytes.next()?);
     } else if engine.layout.compact && remainder_bits > 0 {
         pixmap
             .data
-            .push(engine.bytes.incomplete_next(remainder_bits));
+            .push(engine.bytes.read_bits(remainder_bits)? as u8);
     }
 
     #[cfg(feature = "tagging")]
@@ -341,4 +359,6 @@ pub(crate) fn next_pixmap<R: ByteReader, T: TagWriter>(
             .collect();
         info!("Identified pixmap: {:?}", pixmap_bit_string);
     }
+
+    Ok(())
 }
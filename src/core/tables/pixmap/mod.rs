@@ -17,11 +17,14 @@
 pub(crate) mod deserialize;
 pub(crate) mod serialize;
 
+use crate::core::tables::flagged;
 use crate::core::{
-    DeserializeEngine, Pixmap, PixmapTable, SerializeEngine, SerializeError, Table, TagWriter,
+    DeserializeEngine, Pixmap, PixmapTable, PixmapTableLinkFlags, SerializeEngine, SerializeError,
+    Table, TagWriter,
 };
+use crate::Vec;
 
-use crate::core::byte::ByteReader;
+use crate::core::byte::{ByteReader, ByteWriter};
 #[cfg(feature = "tagging")]
 use crate::core::{ByteIndex, Span, TableType, TagKind};
 
@@ -30,8 +33,9 @@ pub(crate) use serialize::*;
 
 impl Table for PixmapTable {
     fn deserialize<R: ByteReader, T: TagWriter>(
+        &mut self,
         engine: &mut DeserializeEngine<R, T>,
-    ) -> Result<Self, crate::core::DeserializeError> {
+    ) -> Result<(), crate::core::DeserializeError> {
         #[cfg(feature = "tagging")]
         let table_start = engine.bytes.byte_index();
         #[cfg(feature = "tagging")]
@@ -44,12 +48,11 @@ impl Table for PixmapTable {
             engine.bytes.byte_index(),
         );
 
-        let mut pixmap_table = PixmapTable::default();
-        pixmap_table.next_modifier_flags(engine);
-        pixmap_table.next_configurations(engine);
-        pixmap_table.next_table_links(engine);
+        self.next_modifier_flags(engine)?;
+        self.next_configurations(engine)?;
+        self.next_table_links(engine)?;
 
-        let pixmap_count = engine.bytes.next();
+        let pixmap_count = engine.bytes.next()?;
         #[cfg(feature = "tagging")]
         engine.tags.tag_byte(
             TagKind::PixmapTablePixmapCount {
@@ -60,25 +63,23 @@ impl Table for PixmapTable {
         );
 
         for index in 0..pixmap_count {
-            #[cfg(feature = "tagging")]
-            {
-                engine.tagging_data.current_record_index = index;
-            }
+            engine.tagging_data.current_record_index = index;
+            engine.tagging_data.in_record = true;
             #[cfg(feature = "tagging")]
             let pixmap_start = engine.bytes.byte_index();
 
             let mut pixmap = Pixmap::default();
-            next_width(engine, &mut pixmap, pixmap_table.constant_width);
-            next_height(engine, &mut pixmap, pixmap_table.constant_height);
-            next_bits_per_pixel(engine, &mut pixmap, pixmap_table.constant_bits_per_pixel);
+            next_width(engine, &mut pixmap, self.constant_width)?;
+            next_height(engine, &mut pixmap, self.constant_height)?;
+            next_bits_per_pixel(engine, &mut pixmap, self.constant_bits_per_pixel)?;
             next_pixmap(
                 engine,
                 &mut pixmap,
-                pixmap_table.constant_width,
-                pixmap_table.constant_height,
-                pixmap_table.constant_bits_per_pixel,
-            );
-            pixmap_table.pixmaps.push(pixmap);
+                self.constant_width,
+                self.constant_height,
+                self.constant_bits_per_pixel,
+            )?;
+            self.pixmaps.push(pixmap);
 
             #[cfg(feature = "tagging")]
             engine.tags.tag_span(
@@ -98,7 +99,7 @@ impl Table for PixmapTable {
             Span::new(table_start, engine.bytes.byte_index()),
         );
 
-        Ok(pixmap_table)
+        Ok(())
     }
     fn serialize<T: TagWriter>(
         &self,
@@ -109,7 +110,7 @@ impl Table for PixmapTable {
 
         self.push_table_identifier(engine);
         self.push_modifier_flags(engine);
-        self.push_configurations(engine);
+        self.push_configurations(engine)?;
         self.push_table_links(engine)?;
 
         if self.pixmaps.len() > 255 {
@@ -125,20 +126,17 @@ impl Table for PixmapTable {
             engine.bytes.byte_index(),
         );
         for (index, pixmap) in self.pixmaps.iter().enumerate() {
-            #[cfg(feature = "tagging")]
-            {
-                engine.tagging_data.current_record_index = index as u8;
-            }
+            engine.tagging_data.current_record_index = index as u8;
             #[cfg(feature = "tagging")]
             let pixmap_start = engine.bytes.byte_index();
 
-            push_width(engine, self.constant_width, pixmap.custom_width);
-            push_height(engine, self.constant_height, pixmap.custom_height);
+            push_width(engine, self.constant_width, pixmap.custom_width)?;
+            push_height(engine, self.constant_height, pixmap.custom_height)?;
             push_bits_per_pixel(
                 engine,
                 self.constant_bits_per_pixel,
                 pixmap.custom_bits_per_pixel,
-            );
+            )?;
             push_pixmap(
                 engine,
                 self.constant_width,
@@ -168,3 +166,227 @@ impl Table for PixmapTable {
         Ok(())
     }
 }
+
+impl PixmapTable {
+    /// The color tables this table links, or `None` unless
+    /// [`PixmapTableLinkFlags::LinkColorTables`] is set.
+    pub(crate) fn effective_links(&self) -> Option<&[u8]> {
+        flagged(
+            self.link_flags
+                .contains(PixmapTableLinkFlags::LinkColorTables),
+            self.color_table_indexes.as_deref(),
+        )
+    }
+
+    /// Resolves the width, height and bits per pixel of `pixmap`, preferring this table's
+    /// constant values over the pixmap's custom ones.
+    pub(crate) fn pixmap_dimensions(&self, pixmap: &Pixmap) -> Option<(u8, u8, u8)> {
+        Some((
+            self.constant_width.or(pixmap.custom_width)?,
+            self.constant_height.or(pixmap.custom_height)?,
+            self.constant_bits_per_pixel
+                .or(pixmap.custom_bits_per_pixel)?,
+        ))
+    }
+
+    /// Resolves pixmap `pixmap_index` with its width, height and bits per pixel, see
+    /// [`PixmapTable::pixmap_dimensions`]. Returns `None` if the bits per pixel are not within
+    /// `1`-`8`, as pixel values are a single byte.
+    fn resolved_pixmap(&self, pixmap_index: usize) -> Option<(&Pixmap, u8, u8, u8)> {
+        let pixmap = self.pixmaps.get(pixmap_index)?;
+        let (width, height, bits_per_pixel) = self.pixmap_dimensions(pixmap)?;
+        if !(1..=8).contains(&bits_per_pixel) {
+            return None;
+        }
+        Some((pixmap, width, height, bits_per_pixel))
+    }
+
+    /// Returns the value of the pixel at column `x` and row `y` of pixmap `pixmap_index`,
+    /// using this table's constant width, height and bits per pixel where set and the
+    /// pixmap's custom ones otherwise. Returns `None` if there is no such pixmap or pixel,
+    /// if its dimensions are set neither on the table nor on the pixmap, or if its bits per
+    /// pixel are not within `1`-`8`.
+    pub fn pixel(&self, pixmap_index: usize, x: u8, y: u8) -> Option<u8> {
+        let (pixmap, width, height, bits_per_pixel) = self.resolved_pixmap(pixmap_index)?;
+        if x >= width || y >= height {
+            return None;
+        }
+        let index = y as usize * width as usize + x as usize;
+        Some(read_pixel(&pixmap.data, index, bits_per_pixel))
+    }
+
+    /// Sets the pixel at column `x` and row `y` of pixmap `pixmap_index` to `value`, see
+    /// [`PixmapTable::pixel`]. Returns `false` without changing anything if there is no such
+    /// pixel, in the same cases as [`PixmapTable::pixel`], or `value` does not fit in the
+    /// pixmap's bits per pixel.
+    pub fn set_pixel(&mut self, pixmap_index: usize, x: u8, y: u8, value: u8) -> bool {
+        let Some((_, width, height, bits_per_pixel)) = self.resolved_pixmap(pixmap_index) else {
+            return false;
+        };
+        if x >= width || y >= height || !fits(value, bits_per_pixel) {
+            return false;
+        }
+
+        let byte_length = pixmap_byte_length(width, height, bits_per_pixel);
+        let index = y as usize * width as usize + x as usize;
+        let pixmap = &mut self.pixmaps[pixmap_index];
+        if pixmap.data.len() < byte_length {
+            pixmap.data.resize(byte_length, 0);
+        }
+        write_pixel(&mut pixmap.data, index, bits_per_pixel, value);
+        true
+    }
+
+    /// Iterates over every pixel value of pixmap `pixmap_index`, row by row. Returns `None`
+    /// in the same cases as [`PixmapTable::pixel`].
+    pub fn pixels(&self, pixmap_index: usize) -> Option<Pixels<'_>> {
+        let (pixmap, width, height, bits_per_pixel) = self.resolved_pixmap(pixmap_index)?;
+        Some(Pixels {
+            data: &pixmap.data,
+            bits_per_pixel,
+            range: 0..width as usize * height as usize,
+        })
+    }
+
+    /// Iterates over the rows of pixmap `pixmap_index`, each yielding its pixel values from
+    /// left to right. Returns `None` in the same cases as [`PixmapTable::pixel`].
+    pub fn rows(&self, pixmap_index: usize) -> Option<Rows<'_>> {
+        let (pixmap, width, height, bits_per_pixel) = self.resolved_pixmap(pixmap_index)?;
+        Some(Rows {
+            data: &pixmap.data,
+            bits_per_pixel,
+            width: width as usize,
+            range: 0..height as usize,
+        })
+    }
+}
+
+impl Pixmap {
+    /// Creates a pixmap with custom dimensions from one value per pixel, row by row, packing
+    /// them at `bits_per_pixel` bits each in the order they are written to a `.spf` file.
+    ///
+    /// Fails with [`SerializeError::InvalidPixmapData`] if `indices` does not hold exactly
+    /// `width * height` values, if a value does not fit in `bits_per_pixel` bits, or if
+    /// `bits_per_pixel` is not within `1`-`8`.
+    pub fn from_indices(
+        width: u8,
+        height: u8,
+        bits_per_pixel: u8,
+        indices: &[u8],
+    ) -> Result<Self, SerializeError> {
+        if !(1..=8).contains(&bits_per_pixel)
+            || indices.len() != width as usize * height as usize
+            || !indices.iter().all(|value| fits(*value, bits_per_pixel))
+        {
+            return Err(SerializeError::InvalidPixmapData);
+        }
+
+        Ok(Pixmap {
+            custom_width: Some(width),
+            custom_height: Some(height),
+            custom_bits_per_pixel: Some(bits_per_pixel),
+            data: pack_pixels(indices, bits_per_pixel),
+        })
+    }
+}
+
+/// An iterator over pixel values of a pixmap, created by [`PixmapTable::pixels`] or
+/// [`Rows`].
+#[derive(Debug, Clone)]
+pub struct Pixels<'a> {
+    data: &'a [u8],
+    bits_per_pixel: u8,
+    range: core::ops::Range<usize>,
+}
+
+impl Iterator for Pixels<'_> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        let index = self.range.next()?;
+        Some(read_pixel(self.data, index, self.bits_per_pixel))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Pixels<'_> {}
+
+/// An iterator over the rows of a pixmap, created by [`PixmapTable::rows`].
+#[derive(Debug, Clone)]
+pub struct Rows<'a> {
+    data: &'a [u8],
+    bits_per_pixel: u8,
+    width: usize,
+    range: core::ops::Range<usize>,
+}
+
+impl<'a> Iterator for Rows<'a> {
+    type Item = Pixels<'a>;
+
+    fn next(&mut self) -> Option<Pixels<'a>> {
+        let row = self.range.next()?;
+        Some(Pixels {
+            data: self.data,
+            bits_per_pixel: self.bits_per_pixel,
+            range: row * self.width..(row + 1) * self.width,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.range.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Rows<'_> {}
+
+/// Returns `true` if `value` can be stored in `bits_per_pixel` bits.
+pub(crate) fn fits(value: u8, bits_per_pixel: u8) -> bool {
+    bits_per_pixel >= 8 || value >> bits_per_pixel == 0
+}
+
+/// Number of bytes needed to store `width * height` pixels of `bits_per_pixel` bits each.
+pub(crate) fn pixmap_byte_length(width: u8, height: u8, bits_per_pixel: u8) -> usize {
+    let total_bits = width as usize * height as usize * bits_per_pixel as usize;
+    total_bits.div_ceil(8)
+}
+
+/// Reads pixel `index` from least-significant-bit-first packed `data`. Missing bytes read as zero.
+pub(crate) fn read_pixel(data: &[u8], index: usize, bits_per_pixel: u8) -> u8 {
+    let start = index * bits_per_pixel as usize;
+    let mut value = 0;
+    for bit in 0..bits_per_pixel as usize {
+        let position = start + bit;
+        let set = data
+            .get(position / 8)
+            .map_or(0, |byte| (byte >> (position % 8)) & 1);
+        value |= set << bit;
+    }
+    value
+}
+
+/// Writes pixel `index` into least-significant-bit-first packed `data`, which must be long
+/// enough to hold it.
+pub(crate) fn write_pixel(data: &mut [u8], index: usize, bits_per_pixel: u8, value: u8) {
+    let start = index * bits_per_pixel as usize;
+    for bit in 0..bits_per_pixel as usize {
+        let position = start + bit;
+        let mask = 1 << (position % 8);
+        if (value >> bit) & 1 == 1 {
+            data[position / 8] |= mask;
+        } else {
+            data[position / 8] &= !mask;
+        }
+    }
+}
+
+/// Packs one value per pixel at `bits_per_pixel` bits each, least significant bit first.
+pub(crate) fn pack_pixels(pixels: &[u8], bits_per_pixel: u8) -> Vec<u8> {
+    let mut writer = ByteWriter::new();
+    for pixel in pixels {
+        writer.write_bits(*pixel as u32, bits_per_pixel);
+    }
+    writer.into_bytes()
+}
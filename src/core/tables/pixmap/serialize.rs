@@ -15,12 +15,13 @@
  */
 
 use crate::core::{
-    Pixmap, PixmapTable, SerializeEngine, SerializeError, TableIdentifier, TagWriter,
+    Pixmap, PixmapTable, PixmapTableConfigurationFlags, PixmapTableLinkFlags, SerializeEngine,
+    SerializeError, TableIdentifier, TableType, TagWriter,
 };
 use crate::{format, vec, String};
 
 #[cfg(feature = "tagging")]
-use crate::core::{PixmapTableConfigurationFlags, PixmapTableLinkFlags, Span, TableType, TagKind};
+use crate::core::{Span, TagKind};
 
 #[cfg(feature = "log")]
 pub(crate) use log::*;
@@ -47,11 +48,42 @@ impl PixmapTable {
             engine.bytes.byte_index(),
         );
     }
-    pub(crate) fn push_configurations<T: TagWriter>(&self, engine: &mut SerializeEngine<T>) {
+    pub(crate) fn push_configurations<T: TagWriter>(
+        &self,
+        engine: &mut SerializeEngine<T>,
+    ) -> Result<(), SerializeError> {
+        for (flag, value, field) in [
+            (
+                PixmapTableConfigurationFlags::ConstantWidth,
+                self.constant_width,
+                "constant_width",
+            ),
+            (
+                PixmapTableConfigurationFlags::ConstantHeight,
+                self.constant_height,
+                "constant_height",
+            ),
+            (
+                PixmapTableConfigurationFlags::ConstantBitsPerPixel,
+                self.constant_bits_per_pixel,
+                "constant_bits_per_pixel",
+            ),
+        ] {
+            engine.check_table_field(
+                TableType::Pixmap,
+                field,
+                self.configuration_flags.contains(flag),
+                value.is_some(),
+            )?;
+        }
+
         #[cfg(feature = "tagging")]
         let configurations_start = engine.bytes.byte_index();
 
-        engine.bytes.push(self.configuration_flags.bits());
+        engine.push_flags(
+            self.configuration_flags.bits(),
+            PixmapTableConfigurationFlags::all().bits(),
+        );
         #[cfg(feature = "tagging")]
         engine.tags.tag_bitflag(
             TagKind::PixmapTableConfigurationFlags {
@@ -133,6 +165,8 @@ impl PixmapTable {
                 Span::new(configurations_start, engine.bytes.byte_index()),
             );
         }
+
+        Ok(())
     }
     pub(crate) fn push_table_links<T: TagWriter>(
         &self,
@@ -141,8 +175,16 @@ impl PixmapTable {
         #[cfg(feature = "tagging")]
         let table_links_start = engine.bytes.byte_index();
 
+        engine.check_table_field(
+            TableType::Pixmap,
+            "color_table_indexes",
+            self.link_flags
+                .contains(PixmapTableLinkFlags::LinkColorTables),
+            self.color_table_indexes.is_some(),
+        )?;
+
         // Table relations
-        engine.bytes.push(self.link_flags.bits());
+        engine.push_flags(self.link_flags.bits(), PixmapTableLinkFlags::all().bits());
         #[cfg(feature = "tagging")]
         engine.tags.tag_bitflag(
             TagKind::PixmapTableLinkFlags {
@@ -226,9 +268,10 @@ pub(crate) fn push_width<T: TagWriter>(
     engine: &mut SerializeEngine<T>,
     constant_width: Option<u8>,
     custom_width: Option<u8>,
-) {
+) -> Result<(), SerializeError> {
     if constant_width.is_none() {
-        let width = custom_width.unwrap();
+        let width = custom_width
+            .ok_or_else(|| engine.missing_record_field(TableType::Pixmap, "custom_width"))?;
         engine.bytes.push(width);
         #[cfg(feature = "tagging")]
         engine.tags.tag_byte(
@@ -249,15 +292,18 @@ pub(crate) fn push_width<T: TagWriter>(
             )
         }
     }
+
+    Ok(())
 }
 
 pub(crate) fn push_height<T: TagWriter>(
     engine: &mut SerializeEngine<T>,
     constant_height: Option<u8>,
     custom_height: Option<u8>,
-) {
+) -> Result<(), SerializeError> {
     if constant_height.is_none() {
-        let height = custom_height.unwrap();
+        let height = custom_height
+            .ok_or_else(|| engine.missing_record_field(TableType::Pixmap, "custom_height"))?;
         engine.bytes.push(height);
         #[cfg(feature = "tagging")]
         engine.tags.tag_byte(
@@ -278,15 +324,19 @@ pub(crate) fn push_height<T: TagWriter>(
             )
         }
     }
+
+    Ok(())
 }
 
 pub(crate) fn push_bits_per_pixel<T: TagWriter>(
     engine: &mut SerializeEngine<T>,
     constant_bits_per_pixel: Option<u8>,
     custom_bits_per_pixel: Option<u8>,
-) {
+) -> Result<(), SerializeError> {
     if constant_bits_per_pixel.is_none() {
-        let bits_per_pixel = custom_bits_per_pixel.unwrap();
+        let bits_per_pixel = custom_bits_per_pixel.ok_or_else(|| {
+            engine.missing_record_field(TableType::Pixmap, "custom_bits_per_pixel")
+        })?;
         engine.bytes.push(bits_per_pixel);
         #[cfg(feature = "tagging")]
         engine.tags.tag_byte(
@@ -307,6 +357,8 @@ pub(crate) fn push_bits_per_pixel<T: TagWriter>(
             )
         }
     }
+
+    Ok(())
 }
 
 pub(crate) fn push_pixmap<T: TagWriter>(
@@ -324,9 +376,13 @@ pub(crate) fn push_pixmap<T: TagWriter>(
 
     let bits_per_pixel = constant_bits_per_pixel
         .or(pixmap.custom_bits_per_pixel)
-        .unwrap();
-    let width = constant_width.or(pixmap.custom_width).unwrap();
-    let height = constant_height.or(pixmap.custom_height).unwrap();
+        .ok_or_else(|| engine.missing_record_field(TableType::Pixmap, "custom_bits_per_pixel"))?;
+    let width = constant_width
+        .or(pixmap.custom_width)
+        .ok_or_else(|| engine.missing_record_field(TableType::Pixmap, "custom_width"))?;
+    let height = constant_height
+        .or(pixmap.custom_height)
+        .ok_or_else(|| engine.missing_record_field(TableType::Pixmap, "custom_height"))?;
 
     let pixels_used = width as usize * height as usize;
     let total_bits = pixels_used * bits_per_pixel as usize;
@@ -337,6 +393,8 @@ pub(crate) fn push_pixmap<T: TagWriter>(
 
     if pixmap.data.len() > bytes_used {
         return Err(SerializeError::StaticVectorTooLarge);
+    } else if pixmap.data.len() < bytes_used {
+        return Err(SerializeError::InvalidPixmapData);
     }
 
     for index in 0..complete_bytes_used {
@@ -354,7 +412,7 @@ pub(crate) fn push_pixmap<T: TagWriter>(
             }
             engine
                 .bytes
-                .incomplete_push(remainder_byte, remainder_bits as u8);
+                .write_bits(remainder_byte as u32, remainder_bits as u8);
         } else {
             engine.bytes.push(remainder_byte);
         }
@@ -0,0 +1,569 @@
+/*
+ * Copyright 2025 SimplePixelFont
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::core::tables::pixmap::{pixmap_byte_length, read_pixel};
+use crate::core::*;
+use crate::{format, String, Vec};
+
+/// How serious a [`Diagnostic`] reported by [`Layout::validate`] is.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The layout is usable, but something in it is ignored or likely unintended.
+    Warning,
+    /// The layout cannot be serialized, or references data that does not exist.
+    Error,
+}
+
+impl core::fmt::Display for Severity {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A single problem found in a [`Layout`], located by table and record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// How serious this problem is.
+    pub severity: Severity,
+    /// The kind of table the problem was found in, if it belongs to a table.
+    pub table_type: Option<TableType>,
+    /// Index of that table within its [`Layout`] vector.
+    pub table_index: Option<usize>,
+    /// Index of the record within that table, if the problem belongs to a single record.
+    pub record_index: Option<usize>,
+    /// Human readable description of the problem.
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Returns `true` if this diagnostic has [`Severity::Error`].
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+}
+
+impl core::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}: ", self.severity)?;
+        if let Some(table_type) = self.table_type {
+            write!(f, "{}", table_type)?;
+            if let Some(table_index) = self.table_index {
+                write!(f, " {}", table_index)?;
+            }
+            if let Some(record_index) = self.record_index {
+                write!(f, ", record {}", record_index)?;
+            }
+            write!(f, ": ")?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+impl From<DeserializeErrorContext> for Diagnostic {
+    fn from(context: DeserializeErrorContext) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            table_type: context.table_type,
+            table_index: context.table_index.map(usize::from),
+            record_index: context.record_index.map(usize::from),
+            message: format!("{} at {}", context.error, context.byte_index),
+        }
+    }
+}
+
+struct Validator<'a> {
+    layout: &'a Layout,
+    diagnostics: Vec<Diagnostic>,
+    table_type: TableType,
+    table_index: usize,
+}
+
+impl Validator<'_> {
+    fn report(&mut self, severity: Severity, record_index: Option<usize>, message: String) {
+        self.diagnostics.push(Diagnostic {
+            severity,
+            table_type: Some(self.table_type),
+            table_index: Some(self.table_index),
+            record_index,
+            message,
+        });
+    }
+
+    fn error(&mut self, record_index: Option<usize>, message: String) {
+        self.report(Severity::Error, record_index, message);
+    }
+
+    fn warning(&mut self, record_index: Option<usize>, message: String) {
+        self.report(Severity::Warning, record_index, message);
+    }
+
+    /// Checks that a flag and the optional field it controls agree. A field set without its
+    /// flag is an error when `flag_required`, as serializing it fails with
+    /// [`SerializeError::UnflaggedField`].
+    fn flagged(
+        &mut self,
+        record_index: Option<usize>,
+        flag: &str,
+        flag_set: bool,
+        field: &str,
+        field_set: bool,
+        flag_required: bool,
+    ) {
+        if flag_set && !field_set {
+            self.error(
+                record_index,
+                format!("{} is set but {} is missing", flag, field),
+            );
+        } else if !flag_set && field_set && flag_required {
+            self.error(
+                record_index,
+                format!(
+                    "{} is present but {} is not set, so it cannot be written",
+                    field, flag
+                ),
+            );
+        } else if !flag_set && field_set {
+            self.warning(
+                record_index,
+                format!(
+                    "{} is present but {} is not set, it will be ignored",
+                    field, flag
+                ),
+            );
+        }
+    }
+
+    /// Checks that a table's link flag and index list agree, and that every linked table
+    /// exists. Returns the links to follow, or `None` if the table links nothing usable.
+    fn links<'b>(
+        &mut self,
+        flag: &str,
+        flag_set: bool,
+        indexes: &'b Option<Vec<u8>>,
+        linked_type: TableType,
+        available: usize,
+    ) -> Option<&'b [u8]> {
+        self.flagged(None, flag, flag_set, "indexes", indexes.is_some(), true);
+        let indexes = indexes.as_deref().filter(|_| flag_set)?;
+        for index in indexes {
+            if *index as usize >= available {
+                self.error(
+                    None,
+                    format!(
+                        "links {} {} but the layout only has {}",
+                        linked_type, index, available
+                    ),
+                );
+            }
+        }
+        Some(indexes)
+    }
+
+    fn character_table(&mut self, table: &CharacterTable) {
+        let pixmap_tables = self.links(
+            "CharacterTableLinkFlags::LinkPixmapTables",
+            table
+                .link_flags
+                .contains(CharacterTableLinkFlags::LinkPixmapTables),
+            &table.pixmap_table_indexes,
+            TableType::Pixmap,
+            self.layout.pixmap_tables.len(),
+        );
+
+        let use_constant_code_point_count = table
+            .configuration_flags
+            .contains(CharacterTableConfigurationFlags::ConstantCodePointCount);
+        self.flagged(
+            None,
+            "CharacterTableConfigurationFlags::ConstantCodePointCount",
+            use_constant_code_point_count,
+            "constant_code_point_count",
+            table.constant_code_point_count.is_some(),
+            true,
+        );
+
+        let use_advance_x = table
+            .modifier_flags
+            .contains(CharacterTableModifierFlags::UseAdvanceX);
+        let use_pixmap_index = table
+            .modifier_flags
+            .contains(CharacterTableModifierFlags::UsePixmapIndex);
+        let use_pixmap_table_index = table
+            .modifier_flags
+            .contains(CharacterTableModifierFlags::UsePixmapTableIndex);
+
+        for (index, character) in table.characters.iter().enumerate() {
+            let record = Some(index);
+
+            let code_point_count = character.code_points.chars().count();
+            if code_point_count == 0 {
+                self.error(record, "code points are empty".into());
+            } else if character.code_points.contains('\0') {
+                self.error(record, "code points contain a null character".into());
+            }
+            if let Some(constant_code_point_count) = table.constant_code_point_count {
+                if code_point_count != constant_code_point_count as usize {
+                    self.error(
+                        record,
+                        format!(
+                            "has {} code points but the constant code point count is {}",
+                            code_point_count, constant_code_point_count
+                        ),
+                    );
+                }
+            }
+            if table.characters[..index]
+                .iter()
+                .any(|other| other.code_points == character.code_points)
+            {
+                self.warning(
+                    record,
+                    format!(
+                        "code points {:?} are already mapped by an earlier record",
+                        character.code_points
+                    ),
+                );
+            }
+
+            self.flagged(
+                record,
+                "CharacterTableModifierFlags::UseAdvanceX",
+                use_advance_x,
+                "advance_x",
+                character.advance_x.is_some(),
+                false,
+            );
+            self.flagged(
+                record,
+                "CharacterTableModifierFlags::UsePixmapIndex",
+                use_pixmap_index,
+                "pixmap_index",
+                character.pixmap_index.is_some(),
+                false,
+            );
+            self.flagged(
+                record,
+                "CharacterTableModifierFlags::UsePixmapTableIndex",
+                use_pixmap_table_index,
+                "pixmap_table_index",
+                character.pixmap_table_index.is_some(),
+                false,
+            );
+
+            let Some(pixmap_tables) = pixmap_tables else {
+                continue;
+            };
+            let pixmap_index = match character.pixmap_index {
+                Some(pixmap_index) if use_pixmap_index => pixmap_index as usize,
+                _ => index,
+            };
+            let layout = self.layout;
+            let has_pixmap = |link: &u8| {
+                layout
+                    .pixmap_tables
+                    .get(*link as usize)
+                    .is_some_and(|pixmap_table| pixmap_index < pixmap_table.pixmaps.len())
+            };
+
+            match character.pixmap_table_index {
+                Some(pixmap_table_index) if use_pixmap_table_index => {
+                    match pixmap_tables.get(pixmap_table_index as usize) {
+                        None => self.error(
+                            record,
+                            format!(
+                                "pixmap table index {} is out of range, the table links {} pixmap tables",
+                                pixmap_table_index,
+                                pixmap_tables.len()
+                            ),
+                        ),
+                        Some(link) if !has_pixmap(link) => self.error(
+                            record,
+                            format!("pixmap table {} has no pixmap at index {}", link, pixmap_index),
+                        ),
+                        Some(_) => {}
+                    }
+                }
+                _ => {
+                    if !pixmap_tables.iter().any(has_pixmap) {
+                        self.error(
+                            record,
+                            format!(
+                                "no linked pixmap table has a pixmap at index {}",
+                                pixmap_index
+                            ),
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    fn pixmap_table(&mut self, table: &PixmapTable) {
+        let color_tables = self.links(
+            "PixmapTableLinkFlags::LinkColorTables",
+            table
+                .link_flags
+                .contains(PixmapTableLinkFlags::LinkColorTables),
+            &table.color_table_indexes,
+            TableType::Color,
+            self.layout.color_tables.len(),
+        );
+        let palette_size = color_tables.map(|links| {
+            links
+                .iter()
+                .filter_map(|link| self.layout.color_tables.get(*link as usize))
+                .map(|color_table| color_table.colors.len())
+                .sum::<usize>()
+        });
+
+        for (flag, name, field, value) in [
+            (
+                PixmapTableConfigurationFlags::ConstantWidth,
+                "PixmapTableConfigurationFlags::ConstantWidth",
+                "constant_width",
+                table.constant_width,
+            ),
+            (
+                PixmapTableConfigurationFlags::ConstantHeight,
+                "PixmapTableConfigurationFlags::ConstantHeight",
+                "constant_height",
+                table.constant_height,
+            ),
+            (
+                PixmapTableConfigurationFlags::ConstantBitsPerPixel,
+                "PixmapTableConfigurationFlags::ConstantBitsPerPixel",
+                "constant_bits_per_pixel",
+                table.constant_bits_per_pixel,
+            ),
+        ] {
+            self.flagged(
+                None,
+                name,
+                table.configuration_flags.contains(flag),
+                field,
+                value.is_some(),
+                true,
+            );
+        }
+
+        for (index, pixmap) in table.pixmaps.iter().enumerate() {
+            let record = Some(index);
+            let Some((width, height, bits_per_pixel)) = table.pixmap_dimensions(pixmap) else {
+                self.error(
+                    record,
+                    "width, height or bits per pixel is set neither on the table nor on the pixmap"
+                        .into(),
+                );
+                continue;
+            };
+
+            if !(1..=8).contains(&bits_per_pixel) {
+                self.error(
+                    record,
+                    format!(
+                        "bits per pixel is {}, only 1-8 are supported",
+                        bits_per_pixel
+                    ),
+                );
+                continue;
+            }
+
+            let byte_length = pixmap_byte_length(width, height, bits_per_pixel);
+            if pixmap.data.len() != byte_length {
+                self.error(
+                    record,
+                    format!(
+                        "data is {} bytes long but a {}x{} pixmap at {} bits per pixel needs {}",
+                        pixmap.data.len(),
+                        width,
+                        height,
+                        bits_per_pixel,
+                        byte_length
+                    ),
+                );
+                continue;
+            }
+
+            let pixel_count = width as usize * height as usize;
+            let remainder_bits = (pixel_count * bits_per_pixel as usize) % 8;
+            if self.layout.compact
+                && remainder_bits > 0
+                && pixmap.data[byte_length - 1] >> remainder_bits != 0
+            {
+                self.error(
+                    record,
+                    "data sets bits past the last pixel, which a compact layout cannot store"
+                        .into(),
+                );
+            }
+
+            let Some(palette_size) = palette_size else {
+                continue;
+            };
+            let out_of_range = (0..pixel_count)
+                .map(|pixel| (pixel, read_pixel(&pixmap.data, pixel, bits_per_pixel)))
+                .find(|(_, value)| *value as usize >= palette_size);
+            if let Some((pixel, value)) = out_of_range {
+                self.error(
+                    record,
+                    format!(
+                        "pixel {} has value {} but the linked color tables only hold {} colors",
+                        pixel, value, palette_size
+                    ),
+                );
+            }
+        }
+    }
+
+    fn color_table(&mut self, table: &ColorTable) {
+        self.flagged(
+            None,
+            "ColorTableConfigurationFlags::ConstantAlpha",
+            table
+                .configuration_flags
+                .contains(ColorTableConfigurationFlags::ConstantAlpha),
+            "constant_alpha",
+            table.constant_alpha.is_some(),
+            true,
+        );
+        let use_color_type = table
+            .modifier_flags
+            .contains(ColorTableModifierFlags::UseColorType);
+
+        for (index, color) in table.colors.iter().enumerate() {
+            self.flagged(
+                Some(index),
+                "ColorTableModifierFlags::UseColorType",
+                use_color_type,
+                "color_type",
+                color.color_type.is_some(),
+                false,
+            );
+            if table.constant_alpha.is_none() && color.custom_alpha.is_none() {
+                self.error(
+                    Some(index),
+                    "custom_alpha is missing and the table has no constant_alpha".into(),
+                );
+            }
+        }
+    }
+
+    fn font_table(&mut self, table: &FontTable) {
+        let character_tables = self.links(
+            "FontTableLinkFlags::LinkCharacterTables",
+            table
+                .link_flags
+                .contains(FontTableLinkFlags::LinkCharacterTables),
+            &table.character_table_indexes,
+            TableType::Character,
+            self.layout.character_tables.len(),
+        );
+        let linked_count = character_tables.map_or(0, |links| links.len());
+
+        for (index, font) in table.fonts.iter().enumerate() {
+            if font.name.is_empty() || font.author.is_empty() {
+                self.error(Some(index), "name or author is empty".into());
+            } else if font.name.contains('\0') || font.author.contains('\0') {
+                self.error(
+                    Some(index),
+                    "name or author contains a null character".into(),
+                );
+            }
+            for linked in &font.linked_character_table_indexes {
+                if *linked as usize >= linked_count {
+                    self.error(
+                        Some(index),
+                        format!(
+                            "linked character table index {} is out of range, the table links {} character tables",
+                            linked, linked_count
+                        ),
+                    );
+                }
+            }
+        }
+    }
+}
+
+impl Layout {
+    /// Checks that every link and record index in this layout points at an existing table or
+    /// record, that flags agree with the fields they control, and that every pixel value fits
+    /// in its linked color tables. An empty result means no problems were found.
+    pub fn validate(&self) -> Vec<Diagnostic> {
+        let mut validator = Validator {
+            layout: self,
+            diagnostics: Vec::new(),
+            table_type: TableType::Character,
+            table_index: 0,
+        };
+
+        for (index, table) in self.character_tables.iter().enumerate() {
+            validator.table_type = TableType::Character;
+            validator.table_index = index;
+            validator.character_table(table);
+        }
+        for (index, table) in self.pixmap_tables.iter().enumerate() {
+            validator.table_type = TableType::Pixmap;
+            validator.table_index = index;
+            validator.pixmap_table(table);
+        }
+        for (index, table) in self.color_tables.iter().enumerate() {
+            validator.table_type = TableType::Color;
+            validator.table_index = index;
+            validator.color_table(table);
+        }
+        for (index, table) in self.font_tables.iter().enumerate() {
+            validator.table_type = TableType::Font;
+            validator.table_index = index;
+            validator.font_table(table);
+        }
+        for feature in self.features() {
+            if !self.version.supports(feature) {
+                validator.diagnostics.push(Diagnostic {
+                    severity: Severity::Error,
+                    table_type: None,
+                    table_index: None,
+                    record_index: None,
+                    message: format!(
+                        "{} require {} but the layout is {}",
+                        feature,
+                        feature.introduced_in(),
+                        self.version
+                    ),
+                });
+            }
+        }
+        for (index, table) in self.unknown_tables.iter().enumerate() {
+            if table.identifier < RawTable::MIN_IDENTIFIER {
+                validator.diagnostics.push(Diagnostic {
+                    severity: Severity::Error,
+                    table_type: None,
+                    table_index: Some(index),
+                    record_index: None,
+                    message: format!(
+                        "unknown table {} has identifier {:#04x}, below the extension range",
+                        index, table.identifier
+                    ),
+                });
+            }
+        }
+
+        validator.diagnostics
+    }
+}
@@ -0,0 +1,243 @@
+/*
+ * Copyright 2025 SimplePixelFont
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use crate::core::*;
+use crate::{format, String};
+
+fn field<T: PartialEq>(path: &str, name: &str, a: &T, b: &T) -> Option<String> {
+    (a != b).then(|| format!("{}{}", path, name))
+}
+
+fn records<T>(
+    path: &str,
+    name: &str,
+    a: &[T],
+    b: &[T],
+    difference: impl Fn(&str, &T, &T) -> Option<String>,
+) -> Option<String> {
+    if a.len() != b.len() {
+        return Some(format!("{}{}.len()", path, name));
+    }
+    a.iter()
+        .zip(b)
+        .enumerate()
+        .find_map(|(index, (a, b))| difference(&format!("{}{}[{}].", path, name, index), a, b))
+}
+
+fn character_table(path: &str, a: &CharacterTable, b: &CharacterTable) -> Option<String> {
+    field(path, "modifier_flags", &a.modifier_flags, &b.modifier_flags)
+        .or_else(|| {
+            field(
+                path,
+                "configuration_flags",
+                &a.configuration_flags,
+                &b.configuration_flags,
+            )
+        })
+        .or_else(|| {
+            field(
+                path,
+                "constant_code_point_count",
+                &a.constant_code_point_count,
+                &b.constant_code_point_count,
+            )
+        })
+        .or_else(|| field(path, "link_flags", &a.link_flags, &b.link_flags))
+        .or_else(|| {
+            field(
+                path,
+                "pixmap_table_indexes",
+                &a.pixmap_table_indexes,
+                &b.pixmap_table_indexes,
+            )
+        })
+        .or_else(|| {
+            records(
+                path,
+                "characters",
+                &a.characters,
+                &b.characters,
+                |path, a, b| {
+                    field(path, "advance_x", &a.advance_x, &b.advance_x)
+                        .or_else(|| field(path, "pixmap_index", &a.pixmap_index, &b.pixmap_index))
+                        .or_else(|| {
+                            field(
+                                path,
+                                "pixmap_table_index",
+                                &a.pixmap_table_index,
+                                &b.pixmap_table_index,
+                            )
+                        })
+                        .or_else(|| field(path, "code_points", &a.code_points, &b.code_points))
+                },
+            )
+        })
+}
+
+fn pixmap_table(path: &str, a: &PixmapTable, b: &PixmapTable) -> Option<String> {
+    field(
+        path,
+        "configuration_flags",
+        &a.configuration_flags,
+        &b.configuration_flags,
+    )
+    .or_else(|| field(path, "constant_width", &a.constant_width, &b.constant_width))
+    .or_else(|| {
+        field(
+            path,
+            "constant_height",
+            &a.constant_height,
+            &b.constant_height,
+        )
+    })
+    .or_else(|| {
+        field(
+            path,
+            "constant_bits_per_pixel",
+            &a.constant_bits_per_pixel,
+            &b.constant_bits_per_pixel,
+        )
+    })
+    .or_else(|| field(path, "link_flags", &a.link_flags, &b.link_flags))
+    .or_else(|| {
+        field(
+            path,
+            "color_table_indexes",
+            &a.color_table_indexes,
+            &b.color_table_indexes,
+        )
+    })
+    .or_else(|| {
+        records(path, "pixmaps", &a.pixmaps, &b.pixmaps, |path, a, b| {
+            field(path, "custom_width", &a.custom_width, &b.custom_width)
+                .or_else(|| field(path, "custom_height", &a.custom_height, &b.custom_height))
+                .or_else(|| {
+                    field(
+                        path,
+                        "custom_bits_per_pixel",
+                        &a.custom_bits_per_pixel,
+                        &b.custom_bits_per_pixel,
+                    )
+                })
+                .or_else(|| field(path, "data", &a.data, &b.data))
+        })
+    })
+}
+
+fn color_table(path: &str, a: &ColorTable, b: &ColorTable) -> Option<String> {
+    field(path, "modifier_flags", &a.modifier_flags, &b.modifier_flags)
+        .or_else(|| {
+            field(
+                path,
+                "configuration_flags",
+                &a.configuration_flags,
+                &b.configuration_flags,
+            )
+        })
+        .or_else(|| field(path, "constant_alpha", &a.constant_alpha, &b.constant_alpha))
+        .or_else(|| {
+            records(path, "colors", &a.colors, &b.colors, |path, a, b| {
+                field(path, "color_type", &a.color_type, &b.color_type)
+                    .or_else(|| field(path, "custom_alpha", &a.custom_alpha, &b.custom_alpha))
+                    .or_else(|| field(path, "red", &a.red, &b.red))
+                    .or_else(|| field(path, "green", &a.green, &b.green))
+                    .or_else(|| field(path, "blue", &a.blue, &b.blue))
+            })
+        })
+}
+
+fn font_table(path: &str, a: &FontTable, b: &FontTable) -> Option<String> {
+    field(path, "link_flags", &a.link_flags, &b.link_flags)
+        .or_else(|| {
+            field(
+                path,
+                "character_table_indexes",
+                &a.character_table_indexes,
+                &b.character_table_indexes,
+            )
+        })
+        .or_else(|| {
+            records(path, "fonts", &a.fonts, &b.fonts, |path, a, b| {
+                field(path, "name", &a.name, &b.name)
+                    .or_else(|| field(path, "author", &a.author, &b.author))
+                    .or_else(|| field(path, "version", &a.version, &b.version))
+                    .or_else(|| field(path, "font_type", &a.font_type, &b.font_type))
+                    .or_else(|| {
+                        field(
+                            path,
+                            "linked_character_table_indexes",
+                            &a.linked_character_table_indexes,
+                            &b.linked_character_table_indexes,
+                        )
+                    })
+            })
+        })
+}
+
+/// Returns the path of the first field that differs between `a` and `b`, such as
+/// `pixmap_tables[0].pixmaps[1].data`, or `None` if the layouts are equal.
+pub(crate) fn first_difference(a: &Layout, b: &Layout) -> Option<String> {
+    field("", "version", &a.version, &b.version)
+        .or_else(|| field("", "compact", &a.compact, &b.compact))
+        .or_else(|| {
+            records(
+                "",
+                "character_tables",
+                &a.character_tables,
+                &b.character_tables,
+                character_table,
+            )
+        })
+        .or_else(|| {
+            records(
+                "",
+                "pixmap_tables",
+                &a.pixmap_tables,
+                &b.pixmap_tables,
+                pixmap_table,
+            )
+        })
+        .or_else(|| {
+            records(
+                "",
+                "color_tables",
+                &a.color_tables,
+                &b.color_tables,
+                color_table,
+            )
+        })
+        .or_else(|| {
+            records(
+                "",
+                "font_tables",
+                &a.font_tables,
+                &b.font_tables,
+                font_table,
+            )
+        })
+        .or_else(|| {
+            records(
+                "",
+                "unknown_tables",
+                &a.unknown_tables,
+                &b.unknown_tables,
+                |path, a, b| {
+                    field(path, "identifier", &a.identifier, &b.identifier)
+                        .or_else(|| field(path, "data", &a.data, &b.data))
+                },
+            )
+        })
+}
@@ -37,6 +37,7 @@ pub(crate) mod font;
 pub(crate) mod font_table;
 pub(crate) mod pixmap;
 pub(crate) mod pixmap_table;
+pub(crate) mod raw_table;
 
 #[derive(Debug, Clone)]
 pub enum ConversionError {
@@ -74,6 +75,8 @@ impl TryFrom<Layout> for SPFLayout {
             vec_to_raw_with_conversion!(layout.pixmap_tables, SPFPixmapTable);
         let (font_tables_ptr, font_tables_len) =
             vec_to_raw_with_conversion!(layout.font_tables, SPFFontTable);
+        let (unknown_tables_ptr, unknown_tables_len) =
+            vec_to_raw_with_conversion!(layout.unknown_tables, SPFRawTable);
 
         Ok(SPFLayout {
             version: layout.version as c_uchar,
@@ -86,6 +89,8 @@ impl TryFrom<Layout> for SPFLayout {
             pixmap_tables_length: pixmap_tables_len as c_ulong,
             font_tables: font_tables_ptr,
             font_tables_length: font_tables_len as c_ulong,
+            unknown_tables: unknown_tables_ptr,
+            unknown_tables_length: unknown_tables_len as c_ulong,
         })
     }
 }
@@ -103,6 +108,8 @@ impl TryInto<Layout> for SPFLayout {
                 vec_from_raw_with_conversion!(self.pixmap_tables, self.pixmap_tables_length);
             let font_tables =
                 vec_from_raw_with_conversion!(self.font_tables, self.font_tables_length);
+            let unknown_tables =
+                vec_from_raw_with_conversion!(self.unknown_tables, self.unknown_tables_length);
 
             let version =
                 Version::try_from(self.version).map_err(|_| ConversionError::UnsupportedVersion)?;
@@ -114,6 +121,7 @@ impl TryInto<Layout> for SPFLayout {
                 color_tables,
                 pixmap_tables,
                 font_tables,
+                unknown_tables,
             })
         }
     }
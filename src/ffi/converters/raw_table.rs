@@ -0,0 +1,46 @@
+/*
+ * Copyright 2025 SimplePixelFont
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use super::*;
+
+impl TryFrom<RawTable> for SPFRawTable {
+    type Error = ConversionError;
+
+    fn try_from(table: RawTable) -> Result<Self, Self::Error> {
+        let (data_ptr, data_len) = vec_to_raw!(table.data);
+        Ok(SPFRawTable {
+            identifier: table.identifier as c_uchar,
+            data: data_ptr,
+            data_length: data_len as c_ulong,
+        })
+    }
+}
+
+impl TryInto<RawTable> for &SPFRawTable {
+    type Error = ConversionError;
+
+    fn try_into(self) -> Result<RawTable, Self::Error> {
+        unsafe {
+            let data = if self.data.is_null() {
+                Vec::new()
+            } else {
+                slice::from_raw_parts(self.data, self.data_length as usize).to_vec()
+            };
+
+            Ok(RawTable::new(self.identifier, data))
+        }
+    }
+}
@@ -31,6 +31,18 @@ impl Default for SPFLayout {
             pixmap_tables_length: 0,
             font_tables: core::ptr::null_mut(),
             font_tables_length: 0,
+            unknown_tables: core::ptr::null_mut(),
+            unknown_tables_length: 0,
+        }
+    }
+}
+
+impl Default for SPFRawTable {
+    fn default() -> Self {
+        SPFRawTable {
+            identifier: u8::default(),
+            data: core::ptr::null_mut(),
+            data_length: 0,
         }
     }
 }
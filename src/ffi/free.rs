@@ -58,6 +58,7 @@ pub unsafe extern "C" fn spf_free_layout(layout: SPFLayout) {
         free_color_tables(layout.color_tables, layout.color_tables_length as usize);
         free_pixmap_tables(layout.pixmap_tables, layout.pixmap_tables_length as usize);
         free_font_tables(layout.font_tables, layout.font_tables_length as usize);
+        free_raw_tables(layout.unknown_tables, layout.unknown_tables_length as usize);
     }
 }
 
@@ -207,3 +208,24 @@ unsafe fn free_font_tables(ptr: *mut SPFFontTable, len: usize) {
         drop(Box::from_raw(core::ptr::slice_from_raw_parts_mut(ptr, len)));
     }
 }
+
+/// Frees an array of [`SPFRawTable`] values along with all nested allocations.
+///
+/// For each table: frees its `data` byte array. Finally frees the tables array itself.
+unsafe fn free_raw_tables(ptr: *mut SPFRawTable, len: usize) {
+    if ptr.is_null() {
+        return;
+    }
+    unsafe {
+        let tables = core::slice::from_raw_parts(ptr, len);
+        for table in tables {
+            if !table.data.is_null() {
+                drop(Box::from_raw(core::ptr::slice_from_raw_parts_mut(
+                    table.data,
+                    table.data_length as usize,
+                )));
+            }
+        }
+        drop(Box::from_raw(core::ptr::slice_from_raw_parts_mut(ptr, len)));
+    }
+}
@@ -120,6 +120,22 @@ pub struct SPFLayout {
     pub font_tables: *mut SPFFontTable,
     /// Number of elements at `font_tables`.
     pub font_tables_length: c_ulong,
+    /// Pointer to the first of `unknown_tables_length` [`SPFRawTable`]s. See [`Layout::unknown_tables`].
+    pub unknown_tables: *mut SPFRawTable,
+    /// Number of elements at `unknown_tables`.
+    pub unknown_tables_length: c_ulong,
+}
+
+#[derive(Debug, Clone)]
+#[repr(C)]
+/// C ABI mirror of [`RawTable`].
+pub struct SPFRawTable {
+    /// See [`RawTable::identifier`].
+    pub identifier: c_uchar,
+    /// Pointer to the first of `data_length` bytes. See [`RawTable::data`].
+    pub data: *mut c_uchar,
+    /// Number of elements at `data`.
+    pub data_length: c_ulong,
 }
 
 #[derive(Debug, Clone)]
@@ -348,6 +364,11 @@ pub struct SPFData {
 /// Status codes returned by all exported FFI functions. `SPFStatus::Ok` (0) indicates success;
 /// all other values indicate a specific failure. The C caller should check this before reading
 /// any out-parameter.
+///
+/// Codes are grouped by where the failure happened: `1`-`9` while reading a `.spf` file,
+/// `10`-`19` while writing one, and `20`-`29` while converting between FFI types and
+/// [`Layout`]. Reading failures added once `1`-`9` were taken use `30`-`39`. Existing codes
+/// never change.
 #[repr(C)]
 pub enum SPFStatus {
     #[doc = include_str!("../../res/snippets/errors/ok.md")]
@@ -364,14 +385,40 @@ pub enum SPFStatus {
     ErrUnsupportedTableIdentifier = 5,
     #[doc = include_str!("../../res/snippets/errors/unsupported_font_type.md")]
     ErrUnsupportedFontType = 6,
+    #[doc = include_str!("../../res/snippets/errors/invalid_utf8.md")]
+    ErrInvalidUtf8 = 7,
+    #[doc = include_str!("../../res/snippets/errors/trailing_data.md")]
+    ErrTrailingData = 8,
+    #[doc = include_str!("../../res/snippets/errors/reserved_bits_set.md")]
+    ErrReservedBitsSet = 9,
     #[doc = include_str!("../../res/snippets/errors/static_vector_too_large.md")]
     ErrStaticVectorTooLarge = 10,
     #[doc = include_str!("../../res/snippets/errors/invalid_pixmap_data.md")]
     ErrInvalidPixmapData = 11,
+    #[doc = include_str!("../../res/snippets/errors/missing_field.md")]
+    ErrMissingField = 12,
+    #[doc = include_str!("../../res/snippets/errors/unsupported_feature.md")]
+    ErrUnsupportedFeature = 13,
+    #[doc = include_str!("../../res/snippets/errors/buffer_full.md")]
+    ErrBufferFull = 14,
+    #[doc = include_str!("../../res/snippets/errors/round_trip_mismatch.md")]
+    ErrRoundTripMismatch = 15,
+    #[doc = include_str!("../../res/snippets/errors/round_trip_unreadable.md")]
+    ErrRoundTripUnreadable = 16,
+    #[doc = include_str!("../../res/snippets/errors/unresolved_character.md")]
+    ErrUnresolvedCharacter = 17,
+    #[doc = include_str!("../../res/snippets/errors/unflagged_field.md")]
+    ErrUnflaggedField = 18,
     #[doc = include_str!("../../res/snippets/errors/conversion_null_error.md")]
     ErrConversionNulError = 20,
     #[doc = include_str!("../../res/snippets/errors/conversion_utf8_error.md")]
     ErrConversionUtf8Error = 21,
+    #[doc = include_str!("../../res/snippets/errors/limit_exceeded.md")]
+    ErrLimitExceeded = 30,
+    #[doc = include_str!("../../res/snippets/errors/io.md")]
+    ErrIo = 31,
+    #[doc = include_str!("../../res/snippets/errors/unsupported_compact_layout.md")]
+    ErrUnsupportedCompactLayout = 32,
 }
 
 impl From<DeserializeError> for SPFStatus {
@@ -379,21 +426,46 @@ impl From<DeserializeError> for SPFStatus {
         match err {
             DeserializeError::UnexpectedEndOfFile => SPFStatus::ErrUnexpectedEndOfFile,
             DeserializeError::InvalidSignature => SPFStatus::ErrInvalidSignature,
-            DeserializeError::UnsupportedVersion => SPFStatus::ErrUnsupportedVersion,
-            DeserializeError::UnsupportedColorType => SPFStatus::ErrUnsupportedColorType,
-            DeserializeError::UnsupportedTableIdentifier => {
+            DeserializeError::UnsupportedVersion { .. } => SPFStatus::ErrUnsupportedVersion,
+            DeserializeError::UnsupportedColorType { .. } => SPFStatus::ErrUnsupportedColorType,
+            DeserializeError::UnsupportedTableIdentifier { .. } => {
                 SPFStatus::ErrUnsupportedTableIdentifier
             }
-            DeserializeError::UnsupportedFontType => SPFStatus::ErrUnsupportedFontType,
+            DeserializeError::UnsupportedFontType { .. } => SPFStatus::ErrUnsupportedFontType,
+            DeserializeError::InvalidUtf8 { .. } => SPFStatus::ErrInvalidUtf8,
+            DeserializeError::TrailingData => SPFStatus::ErrTrailingData,
+            DeserializeError::ReservedBitsSet { .. } => SPFStatus::ErrReservedBitsSet,
+            DeserializeError::LimitExceeded { .. } => SPFStatus::ErrLimitExceeded,
+            DeserializeError::UnsupportedCompactLayout => SPFStatus::ErrUnsupportedCompactLayout,
+            #[cfg(feature = "std")]
+            DeserializeError::Io(_) => SPFStatus::ErrIo,
         }
     }
 }
 
+impl From<DeserializeErrorContext> for SPFStatus {
+    fn from(err: DeserializeErrorContext) -> Self {
+        SPFStatus::from(err.error)
+    }
+}
+
 impl From<SerializeError> for SPFStatus {
     fn from(err: SerializeError) -> Self {
         match err {
             SerializeError::StaticVectorTooLarge => SPFStatus::ErrStaticVectorTooLarge,
             SerializeError::InvalidPixmapData => SPFStatus::ErrInvalidPixmapData,
+            SerializeError::MissingField { .. } => SPFStatus::ErrMissingField,
+            SerializeError::UnflaggedField { .. } => SPFStatus::ErrUnflaggedField,
+            SerializeError::UnsupportedTableIdentifier { .. } => {
+                SPFStatus::ErrUnsupportedTableIdentifier
+            }
+            SerializeError::UnsupportedFeature { .. } => SPFStatus::ErrUnsupportedFeature,
+            SerializeError::BufferFull => SPFStatus::ErrBufferFull,
+            SerializeError::RoundTripMismatch { .. } => SPFStatus::ErrRoundTripMismatch,
+            SerializeError::RoundTripUnreadable(_) => SPFStatus::ErrRoundTripUnreadable,
+            SerializeError::UnresolvedCharacter { .. } => SPFStatus::ErrUnresolvedCharacter,
+            #[cfg(feature = "std")]
+            SerializeError::Io(_) => SPFStatus::ErrIo,
         }
     }
 }
@@ -415,6 +487,8 @@ impl From<converters::ConversionError> for SPFStatus {
 pub enum SPFVersion {
     #[doc = include_str!("../../res/snippets/data_types/Version/FV0.md")]
     FV0 = 0,
+    #[doc = include_str!("../../res/snippets/data_types/Version/FV1.md")]
+    FV1 = 1,
 }
 
 /// Named constants for the `color_type` field of [`SPFColor`].
@@ -32,6 +32,8 @@ pub(crate) extern crate alloc;
 #[cfg(feature = "std")]
 pub(crate) use std::borrow::ToOwned;
 #[cfg(feature = "std")]
+pub(crate) use std::collections::HashMap as Map;
+#[cfg(feature = "std")]
 pub(crate) use std::format;
 #[cfg(feature = "std")]
 pub(crate) use std::string::String;
@@ -43,6 +45,8 @@ pub(crate) use std::vec::Vec;
 #[cfg(not(feature = "std"))]
 pub(crate) use alloc::borrow::ToOwned;
 #[cfg(not(feature = "std"))]
+pub(crate) use alloc::collections::BTreeMap as Map;
+#[cfg(not(feature = "std"))]
 pub(crate) use alloc::format;
 #[cfg(not(feature = "std"))]
 pub(crate) use alloc::string::String;
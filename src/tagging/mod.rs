@@ -21,26 +21,7 @@
 use crate::core::{byte::*, *};
 use crate::{format, String, Vec};
 
-/// A bit-precision position within a `.spf` file's byte stream.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub struct ByteIndex {
-    /// The byte offset from the start of the file.
-    pub byte: usize,
-    /// The bit offset within `byte`, `0`-`7`.
-    pub bit: u8,
-}
-
-impl ByteIndex {
-    /// Constructs a [`ByteIndex`] at an explicit byte and bit offset.
-    pub fn new(byte: usize, bit: u8) -> Self {
-        Self { byte, bit }
-    }
-
-    /// Constructs a [`ByteIndex`] at the start of `byte` (bit `0`).
-    pub fn at_byte(byte: usize) -> Self {
-        Self { byte, bit: 0 }
-    }
-}
+pub use crate::core::{ByteIndex, TableType};
 
 /// A bit-precision byte range tagged as belonging to one [`TagKind`].
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -547,6 +528,14 @@ pub enum TagKind {
         value: u8,
     },
 
+    /// Tags [`RawTable`], from its identifier to the end of its body.
+    RawTable {
+        /// Index of this table within [`Layout::unknown_tables`].
+        index: u8,
+        /// The extension table identifier.
+        identifier: u8,
+    },
+
     /// Tags [`FontTable`].
     FontTable {
         /// Index of this table within its `Layout`.
@@ -703,20 +692,6 @@ pub enum TagKind {
     },
 }
 
-/// Identifies which of the four table kinds a [`TagKind::TableIdentifier`] tag names.
-#[non_exhaustive]
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum TableType {
-    /// A [`CharacterTable`].
-    Character,
-    /// A [`PixmapTable`].
-    Pixmap,
-    /// A [`ColorTable`].
-    Color,
-    /// A [`FontTable`].
-    Font,
-}
-
 /// One [`TagKind`] paired with the byte:bit [`Span`] it was written to or read from.
 #[derive(Debug, Clone)]
 pub struct Tag {
@@ -733,7 +708,7 @@ impl Tag {
     }
 }
 
-impl ByteWriter {
+impl ByteWriter<'_> {
     pub(crate) fn byte_index(&self) -> ByteIndex {
         ByteIndex::new(self.index, self.pointer)
     }
@@ -9,6 +9,7 @@ mod tests {
     use std::io;
 
     use super::common;
+    use spf::core::byte::ByteReader;
     use spf::core::*;
 
     fn init_logger() {
@@ -154,8 +155,1119 @@ mod tests {
         let mut iterator_engine = DeserializeEngine::from_reader(reader);
         deserialize_with_engine(&mut iterator_engine).unwrap();
 
-        //assert_eq!(standard_engine.layout, iterator_engine.layout);
+        assert_eq!(standard_engine.layout, iterator_engine.layout);
 
         Ok(())
     }
+
+    #[test]
+    fn read_truncated_font_data() {
+        init_logger();
+
+        let buffer = layout_to_data(&sample_layout()).unwrap();
+        let directory = TableDirectory::scan(&buffer).unwrap();
+
+        for length in 0..buffer.len() {
+            let result = layout_from_data(&buffer[..length]).map_err(|context| context.error);
+            assert!(
+                matches!(result, Ok(_) | Err(DeserializeError::UnexpectedEndOfFile)),
+                "unexpected result for {} bytes: {:?}",
+                length,
+                result
+            );
+            // Only a cut between tables parses, and then keeps every table it starts.
+            if let Ok(layout) = result {
+                let started = directory
+                    .entries()
+                    .iter()
+                    .filter(|entry| {
+                        entry.offset.byte * 8 + entry.offset.bit as usize + 8 <= length * 8
+                    })
+                    .count();
+                let tables = layout.character_tables.len()
+                    + layout.pixmap_tables.len()
+                    + layout.color_tables.len()
+                    + layout.font_tables.len();
+                assert_eq!(tables, started, "tables read from {} bytes", length);
+            }
+
+            let mut buffer_iter = buffer[..length].iter().copied();
+            let reader = byte::ByteReaderIter::from(&mut buffer_iter, length);
+            let mut iterator_engine = DeserializeEngine::from_reader(reader);
+            let result =
+                deserialize_with_engine(&mut iterator_engine).map_err(|context| context.error);
+            assert!(matches!(
+                result,
+                Ok(_) | Err(DeserializeError::UnexpectedEndOfFile)
+            ));
+        }
+    }
+
+    #[test]
+    fn read_invalid_utf8_code_points() {
+        init_logger();
+
+        let mut layout = sample_layout();
+        layout.compact = false;
+        let mut buffer = layout_to_data(&layout).unwrap();
+
+        let position = buffer.iter().position(|byte| *byte == b'w').unwrap();
+        buffer[position] = 0b11111000;
+
+        assert!(matches!(
+            layout_from_data(&buffer).map_err(|context| context.error),
+            Err(DeserializeError::InvalidUtf8 {
+                table: 0,
+                record: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn validate_layout_references() {
+        init_logger();
+
+        let mut layout = sample_layout();
+        let diagnostics = layout.validate();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].table_type, Some(TableType::Pixmap));
+        assert_eq!(diagnostics[0].table_index, Some(1));
+        assert_eq!(diagnostics[0].record_index, Some(0));
+
+        layout.pixmap_tables[1].pixmaps[0].data = vec![0b00000001, 0, 0, 0];
+        assert!(layout.validate().is_empty());
+
+        layout.character_tables[0].pixmap_table_indexes = Some(vec![7]);
+        layout.font_tables[0].fonts[0].linked_character_table_indexes = vec![1];
+        let diagnostics = layout.validate();
+        assert!(diagnostics.iter().all(Diagnostic::is_error));
+        assert!(diagnostics.iter().any(|diagnostic| diagnostic.table_type
+            == Some(TableType::Character)
+            && diagnostic.record_index.is_none()));
+        assert!(diagnostics
+            .iter()
+            .any(|diagnostic| diagnostic.table_type == Some(TableType::Font)
+                && diagnostic.record_index == Some(0)));
+
+        let mut layout = sample_layout();
+        layout.pixmap_tables[1].pixmaps[0].data = vec![0b00000001, 0, 0, 0];
+        layout.font_tables[0].fonts[0].author = String::new();
+        let diagnostics = layout.validate();
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].is_error());
+        assert_eq!(diagnostics[0].table_type, Some(TableType::Font));
+        assert_eq!(diagnostics[0].record_index, Some(0));
+    }
+
+    #[test]
+    fn write_layout_with_missing_field() {
+        init_logger();
+
+        let mut layout = sample_layout();
+        layout.character_tables[0].modifier_flags = CharacterTableModifierFlags::UseAdvanceX;
+        layout.character_tables[0].characters[0].advance_x = Some(4);
+
+        assert!(matches!(
+            layout_to_data(&layout),
+            Err(SerializeError::MissingField {
+                table_type: TableType::Character,
+                table_index: 0,
+                record_index: Some(1),
+                field: "advance_x",
+            })
+        ));
+    }
+
+    #[test]
+    fn write_normalized_layout() {
+        init_logger();
+
+        let mut layout = sample_layout();
+        for character_table in &mut layout.character_tables {
+            character_table.link_flags = CharacterTableLinkFlags::empty();
+            character_table.modifier_flags = CharacterTableModifierFlags::UseAdvanceX;
+        }
+        for pixmap_table in &mut layout.pixmap_tables {
+            pixmap_table.configuration_flags = PixmapTableConfigurationFlags::empty();
+            pixmap_table.link_flags = PixmapTableLinkFlags::empty();
+        }
+        for font_table in &mut layout.font_tables {
+            font_table.link_flags = FontTableLinkFlags::empty();
+        }
+        assert!(layout.validate().iter().any(Diagnostic::is_error));
+
+        let mut options = SerializeOptions::default();
+        options.normalize = true;
+        assert_eq!(
+            layout_to_data_with_options(&layout, options).unwrap(),
+            layout_to_data(&sample_layout()).unwrap()
+        );
+
+        layout.normalize();
+        assert_eq!(layout.validate(), sample_layout().validate());
+
+        let mut partial = sample_layout();
+        partial.character_tables[0].modifier_flags = CharacterTableModifierFlags::empty();
+        partial.character_tables[0].characters[0].advance_x = Some(4);
+        assert!(matches!(
+            layout_to_data_with_options(&partial, options),
+            Err(SerializeError::MissingField {
+                table_type: TableType::Character,
+                table_index: 0,
+                record_index: Some(1),
+                field: "advance_x",
+            })
+        ));
+        partial.normalize();
+        assert!(partial.character_tables[0]
+            .modifier_flags
+            .contains(CharacterTableModifierFlags::UseAdvanceX));
+        assert_eq!(partial.character_tables[0].characters[0].advance_x, Some(4));
+        assert!(partial.validate().iter().any(Diagnostic::is_error));
+
+        let mut unflagged = sample_layout();
+        unflagged.pixmap_tables[0].configuration_flags = PixmapTableConfigurationFlags::empty();
+        assert!(matches!(
+            layout_to_data(&unflagged),
+            Err(SerializeError::UnflaggedField {
+                table_type: TableType::Pixmap,
+                table_index: 0,
+                record_index: None,
+                field: "constant_height",
+            })
+        ));
+    }
+
+    #[test]
+    fn read_error_context() {
+        init_logger();
+
+        let mut layout = sample_layout();
+        layout.color_tables[0].modifier_flags = ColorTableModifierFlags::UseColorType;
+        for color in &mut layout.color_tables[0].colors {
+            color.color_type = Some(ColorType::Dynamic);
+        }
+
+        let mut engine =
+            SerializeEngine::from_layout_and_tags(&layout, spf::tagging::TagWriterImpl::default());
+        serialize_with_engine(&mut engine).unwrap();
+        let color_type_byte = engine
+            .tags
+            .tags
+            .iter()
+            .find_map(|tag| match tag.kind {
+                spf::tagging::TagKind::ColorColorType { color_index: 1, .. } => {
+                    Some(tag.span.start)
+                }
+                _ => None,
+            })
+            .unwrap();
+
+        let mut buffer = engine.data_owned();
+        buffer[color_type_byte.byte] = 7;
+
+        let context = layout_from_data(&buffer).unwrap_err();
+        assert!(matches!(
+            context.error,
+            DeserializeError::UnsupportedColorType { value: 7 }
+        ));
+        assert_eq!(context.byte_index, color_type_byte);
+        assert_eq!(
+            context.to_string(),
+            format!(
+                "color table 0, color 1, byte {:#x}: unsupported color type 7",
+                color_type_byte.byte
+            )
+        );
+
+        // Limits are checked before reading the table they would be exceeded by, which in
+        // this compact layout starts partway through a byte.
+        let mut buffer = layout_to_data(&sample_layout()).unwrap();
+        let third_table = TableDirectory::scan(&buffer).unwrap().entries()[2].offset;
+        assert_ne!(third_table.bit, 0);
+        let mut options = DeserializeOptions::default();
+        options.limits.max_tables = Some(2);
+        let context = layout_from_data_with_options(&buffer, options).unwrap_err();
+        assert_eq!(context.byte_index, third_table);
+
+        let (byte, bit) = (third_table.byte, third_table.bit);
+        buffer[byte] = buffer[byte] & !(0xff << bit) | (9 << bit);
+        buffer[byte + 1] &= 0xff << bit;
+        let context = layout_from_data(&buffer).unwrap_err();
+        assert!(matches!(
+            context.error,
+            DeserializeError::UnsupportedTableIdentifier { value: 9 }
+        ));
+        assert_eq!(context.byte_index, third_table);
+
+        // Invalid UTF-8 is reported where the character starts, not at its last byte.
+        let mut layout = sample_layout();
+        layout.compact = false;
+        let mut buffer = layout_to_data(&layout).unwrap();
+        let emoji = "😊".as_bytes();
+        let start = buffer
+            .windows(emoji.len())
+            .position(|window| window == emoji)
+            .unwrap();
+        buffer[start + 3] = b'x';
+        let context = layout_from_data(&buffer).unwrap_err();
+        assert!(matches!(
+            context.error,
+            DeserializeError::InvalidUtf8 { .. }
+        ));
+        assert_eq!(context.byte_index, ByteIndex::new(start, 0));
+    }
+
+    #[test]
+    fn read_strict_layout() {
+        init_logger();
+
+        let mut strict = DeserializeOptions::default();
+        strict.strict = true;
+        let buffer = layout_to_data(&sample_layout()).unwrap();
+        assert!(layout_from_data_with_options(&buffer, strict).is_ok());
+
+        // A trailing byte is read as another table in either mode.
+        let mut trailing = buffer.clone();
+        trailing.push(0);
+        assert!(matches!(
+            layout_from_data(&trailing).map_err(|context| context.error),
+            Err(DeserializeError::UnsupportedTableIdentifier { value: 0 })
+        ));
+
+        // The first pixmap table ends partway through a byte, leaving padding bits.
+        let mut layout = Layout::default();
+        layout.compact = true;
+        layout.pixmap_tables = vec![sample_pixmap_table()];
+        let mut padded = layout_to_data(&layout).unwrap();
+        *padded.last_mut().unwrap() |= 0b10000000;
+        assert!(layout_from_data(&padded).is_ok());
+        assert!(matches!(
+            layout_from_data_with_options(&padded, strict).map_err(|context| context.error),
+            Err(DeserializeError::TrailingData)
+        ));
+
+        // The header flags byte follows the four signature bytes and the version byte.
+        let mut reserved = buffer.clone();
+        reserved[5] |= 0b10000000;
+        assert!(layout_from_data(&reserved).is_ok());
+        let context = layout_from_data_with_options(&reserved, strict).unwrap_err();
+        assert!(matches!(
+            context.error,
+            DeserializeError::ReservedBitsSet { value } if value & 0b10000000 != 0
+        ));
+        assert_eq!(context.byte_index.byte, 5);
+    }
+
+    #[test]
+    fn read_lossy_layout() {
+        init_logger();
+
+        let layout = sample_layout();
+        let mut engine =
+            SerializeEngine::from_layout_and_tags(&layout, spf::tagging::TagWriterImpl::default());
+        serialize_with_engine(&mut engine).unwrap();
+        let third_pixmap = engine
+            .tags
+            .tags
+            .iter()
+            .find_map(|tag| match tag.kind {
+                spf::tagging::TagKind::PixmapRecord {
+                    table_index: 0,
+                    pixmap_index: 2,
+                } => Some(tag.span.start),
+                _ => None,
+            })
+            .unwrap();
+
+        let buffer = engine.data_owned();
+        let (_, diagnostics) = layout_from_data_lossy(&buffer);
+        assert!(diagnostics.is_empty());
+
+        let (recovered, diagnostics) = layout_from_data_lossy(&buffer[..=third_pixmap.byte]);
+        assert_eq!(
+            recovered.character_tables[0].characters.len(),
+            layout.character_tables[0].characters.len()
+        );
+        assert_eq!(recovered.pixmap_tables.len(), 1);
+        let pixmaps = &recovered.pixmap_tables[0].pixmaps;
+        assert_eq!(pixmaps.len(), 2);
+        for (recovered, original) in pixmaps.iter().zip(&layout.pixmap_tables[0].pixmaps) {
+            assert_eq!(recovered.data, original.data);
+        }
+
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0].is_error());
+        assert_eq!(diagnostics[0].table_type, Some(TableType::Pixmap));
+        assert_eq!(diagnostics[0].table_index, Some(0));
+        assert_eq!(diagnostics[0].record_index, Some(2));
+    }
+
+    #[test]
+    fn read_layout_with_limits() {
+        init_logger();
+
+        let buffer = layout_to_data(&sample_layout()).unwrap();
+        let read_with = |limits: Limits| {
+            let mut options = DeserializeOptions::default();
+            options.limits = limits;
+            layout_from_data_with_options(&buffer, options).map_err(|context| context.error)
+        };
+
+        let mut limits = Limits::default();
+        limits.max_tables = Some(5);
+        limits.max_pixmap_bytes = Some(13);
+        limits.max_code_points = Some(2);
+        limits.max_string_length = Some(13);
+        assert!(read_with(limits).is_ok());
+
+        let exceeded = |limits: Limits, limit: Limit| {
+            matches!(
+                read_with(limits),
+                Err(DeserializeError::LimitExceeded { which }) if which == limit
+            )
+        };
+        let mut tables = limits;
+        tables.max_tables = Some(4);
+        assert!(exceeded(tables, Limit::Tables));
+        let mut pixmap_bytes = limits;
+        pixmap_bytes.max_pixmap_bytes = Some(12);
+        assert!(exceeded(pixmap_bytes, Limit::PixmapBytes));
+        let mut code_points = limits;
+        code_points.max_code_points = Some(1);
+        assert!(exceeded(code_points, Limit::CodePoints));
+        let mut string_length = limits;
+        string_length.max_string_length = Some(12);
+        assert!(exceeded(string_length, Limit::StringLength));
+    }
+
+    #[test]
+    fn round_trip_unknown_tables() {
+        init_logger();
+
+        let mut layout = sample_layout();
+        layout.version = Version::FV1;
+        layout
+            .unknown_tables
+            .push(RawTable::new(0x80, vec![1, 2, 3]));
+        layout.unknown_tables.push(RawTable::new(0xff, Vec::new()));
+
+        let buffer = layout_to_data(&layout).unwrap();
+        let read = layout_from_data(&buffer).unwrap();
+        assert_eq!(read.unknown_tables.len(), 2);
+        assert_eq!(read.unknown_tables[0].identifier, 0x80);
+        assert_eq!(read.unknown_tables[0].data, vec![1, 2, 3]);
+        assert_eq!(read.unknown_tables[1].identifier, 0xff);
+        assert!(read.unknown_tables[1].data.is_empty());
+        assert_eq!(read.font_tables.len(), layout.font_tables.len());
+        assert_eq!(layout_to_data(&read).unwrap(), buffer);
+
+        #[cfg(feature = "ffi")]
+        {
+            let raw = spf::ffi::SPFLayout::try_from(read.clone()).unwrap();
+            assert_eq!(raw.unknown_tables_length, 2);
+            let converted: Layout = raw.clone().try_into().unwrap();
+            unsafe { spf::ffi::spf_free_layout(raw) };
+            assert_eq!(converted.unknown_tables, read.unknown_tables);
+        }
+
+        layout.unknown_tables[0].identifier = 0x05;
+        assert!(matches!(
+            layout_to_data(&layout),
+            Err(SerializeError::UnsupportedTableIdentifier { value: 0x05 })
+        ));
+    }
+
+    #[test]
+    fn migrate_layout_versions() {
+        init_logger();
+
+        let mut layout = sample_layout();
+        assert!(layout.features().is_empty());
+        layout.migrate_to(Version::FV1).unwrap();
+        layout.migrate_to(Version::FV0).unwrap();
+
+        layout.unknown_tables.push(RawTable::new(0x80, vec![42]));
+        assert!(matches!(
+            layout_to_data(&layout),
+            Err(SerializeError::UnsupportedFeature {
+                version: Version::FV0,
+                feature: VersionFeature::ExtensionTables,
+            })
+        ));
+        assert!(layout.validate().len() > sample_layout().validate().len());
+
+        layout.migrate_to(Version::LATEST).unwrap();
+        assert_eq!(layout.version, Version::FV1);
+        let buffer = layout_to_data(&layout).unwrap();
+
+        let error = layout.migrate_to(Version::FV0).unwrap_err();
+        assert_eq!(error.unsupported, vec![VersionFeature::ExtensionTables]);
+        assert_eq!(layout.version, Version::FV1);
+
+        // An FV0 file may not contain extension tables.
+        let mut downgraded = buffer.clone();
+        downgraded[4] = Version::FV0 as u8;
+        assert!(matches!(
+            layout_from_data(&downgraded).map_err(|context| context.error),
+            Err(DeserializeError::UnsupportedTableIdentifier { value: 0x80 })
+        ));
+
+        // Reserved flag bits survive in FV0, but FV1 writers clear them and readers reject them.
+        let mut layout = sample_layout();
+        layout.character_tables[0].modifier_flags =
+            CharacterTableModifierFlags::from_bits_retain(0b10000000);
+        let buffer = layout_to_data(&layout).unwrap();
+        let decoded = layout_from_data(&buffer).unwrap();
+        assert_eq!(
+            decoded.character_tables[0].modifier_flags.bits(),
+            0b10000000
+        );
+
+        let mut upgraded = buffer.clone();
+        upgraded[4] = Version::FV1 as u8;
+        assert!(matches!(
+            layout_from_data(&upgraded).map_err(|context| context.error),
+            Err(DeserializeError::ReservedBitsSet { value: 0b10000000 })
+        ));
+
+        layout.migrate_to(Version::FV1).unwrap();
+        let decoded = layout_from_data(&layout_to_data(&layout).unwrap()).unwrap();
+        assert!(decoded.character_tables[0].modifier_flags.is_empty());
+    }
+
+    #[test]
+    fn read_layout_from_reader() {
+        init_logger();
+
+        struct FailingReader<'a> {
+            data: &'a [u8],
+        }
+        impl io::Read for FailingReader<'_> {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                if self.data.is_empty() {
+                    return Err(io::Error::new(io::ErrorKind::ConnectionReset, "reset"));
+                }
+                let length = buf.len().min(self.data.len());
+                buf[..length].copy_from_slice(&self.data[..length]);
+                self.data = &self.data[length..];
+                Ok(length)
+            }
+        }
+
+        let buffer = layout_to_data(&sample_layout()).unwrap();
+        let layout = layout_from_reader(io::Cursor::new(&buffer)).unwrap();
+        assert_eq!(layout_to_data(&layout).unwrap(), buffer);
+
+        assert!(matches!(
+            layout_from_reader(io::Cursor::new(&buffer[..buffer.len() / 2]))
+                .map_err(|context| context.error),
+            Err(DeserializeError::UnexpectedEndOfFile)
+        ));
+
+        let context = layout_from_reader(FailingReader {
+            data: &buffer[..buffer.len() / 2],
+        })
+        .unwrap_err();
+        match context.error {
+            DeserializeError::Io(error) => assert_eq!(error.kind(), io::ErrorKind::ConnectionReset),
+            error => panic!("expected an I/O error, got {:?}", error),
+        }
+
+        // Peeking must not consume the error.
+        let mut reader = byte::ByteReaderRead::new(FailingReader { data: &[] });
+        for _ in 0..2 {
+            assert!(matches!(reader.get(), Err(DeserializeError::Io(_))));
+        }
+        assert!(matches!(reader.next(), Err(DeserializeError::Io(_))));
+        assert!(matches!(reader.take_error(), Some(DeserializeError::Io(_))));
+
+        // A reader failing exactly between two tables must not look like the end of the file.
+        let mut layout = Layout::default();
+        layout.color_tables.push(sample_color_table());
+        let boundary = layout_to_data(&layout).unwrap().len();
+        layout.color_tables.push(sample_color_table());
+        let buffer = layout_to_data(&layout).unwrap();
+        for length in boundary - 1..=boundary + 1 {
+            let context = layout_from_reader(FailingReader {
+                data: &buffer[..length],
+            })
+            .unwrap_err();
+            assert!(
+                matches!(context.error, DeserializeError::Io(_)),
+                "reader failing after byte {} gave {:?}",
+                length,
+                context.error
+            );
+        }
+    }
+
+    #[test]
+    fn write_layout_to_sinks() {
+        init_logger();
+
+        // Large enough to be streamed out in several chunks.
+        let mut layout = sample_layout();
+        let pixmap_table = layout.pixmap_tables[0].clone();
+        layout.pixmap_tables = vec![pixmap_table; 200];
+        let buffer = layout_to_data(&layout).unwrap();
+
+        let mut written = Vec::new();
+        layout_to_writer(&layout, &mut written).unwrap();
+        assert_eq!(written, buffer);
+
+        let mut fixed = vec![0; buffer.len()];
+        let mut sink = &mut fixed[..];
+        let mut engine = SerializeEngine::from_layout(&layout);
+        engine.set_sink(&mut sink);
+        serialize_with_engine(&mut engine).unwrap();
+        assert!(engine.data().is_empty());
+        assert_eq!(fixed, buffer);
+
+        let mut too_small = vec![0; buffer.len() - 1];
+        let mut sink = &mut too_small[..];
+        let mut engine = SerializeEngine::from_layout(&layout);
+        engine.set_sink(&mut sink);
+        assert!(matches!(
+            serialize_with_engine(&mut engine),
+            Err(SerializeError::BufferFull)
+        ));
+    }
+
+    #[test]
+    fn read_borrowed_layout() {
+        init_logger();
+
+        let mut layout = sample_layout();
+        layout.compact = false;
+        let buffer = layout_to_data(&layout).unwrap();
+
+        let borrowed = layout_ref_from_data(&buffer).unwrap();
+        let buffer_range = buffer.as_ptr_range();
+        let code_points = borrowed.character_tables[0].characters[2].code_points;
+        assert_eq!(code_points, "😊");
+        assert!(buffer_range.contains(&code_points.as_ptr()));
+        let pixmap = borrowed.pixmap_tables[1].pixmaps[0].data;
+        assert_eq!(pixmap, &layout.pixmap_tables[1].pixmaps[0].data[..]);
+        assert!(buffer_range.contains(&pixmap.as_ptr()));
+        assert_eq!(borrowed.font_tables[0].fonts[0].name, "SampleToyFont");
+        assert_eq!(borrowed.font_tables[0].fonts[0].author, "The-Nice-One");
+
+        assert_eq!(layout_to_data(&borrowed.to_owned()).unwrap(), buffer);
+
+        let mut strict = DeserializeOptions::default();
+        strict.strict = true;
+        assert!(layout_ref_from_data_with_options(&buffer, strict).is_ok());
+        let mut trailing = buffer.clone();
+        trailing.push(0);
+        assert!(matches!(
+            layout_ref_from_data(&trailing).map_err(|context| context.error),
+            Err(DeserializeError::UnsupportedTableIdentifier { value: 0 })
+        ));
+        let mut reserved = buffer.clone();
+        reserved[5] |= 0b10000000;
+        assert!(layout_ref_from_data(&reserved).is_ok());
+        assert!(matches!(
+            layout_ref_from_data_with_options(&reserved, strict).map_err(|context| context.error),
+            Err(DeserializeError::ReservedBitsSet { .. })
+        ));
+
+        let compact = layout_to_data(&sample_layout()).unwrap();
+        assert!(matches!(
+            layout_ref_from_data(&compact).map_err(|context| context.error),
+            Err(DeserializeError::UnsupportedCompactLayout)
+        ));
+    }
+
+    #[test]
+    fn read_tables_through_directory() {
+        init_logger();
+
+        let layout = sample_layout();
+        let buffer = layout_to_data(&layout).unwrap();
+        let directory = TableDirectory::scan(&buffer).unwrap();
+
+        let kinds: Vec<_> = directory
+            .entries()
+            .iter()
+            .map(|entry| (entry.table_type, entry.index))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (Some(TableType::Character), 0),
+                (Some(TableType::Pixmap), 0),
+                (Some(TableType::Pixmap), 1),
+                (Some(TableType::Color), 0),
+                (Some(TableType::Font), 0),
+            ]
+        );
+
+        assert_eq!(
+            directory.pixmap_table(1).unwrap().unwrap(),
+            layout.pixmap_tables[1]
+        );
+        for (index, pixmap) in layout.pixmap_tables[0].pixmaps.iter().enumerate() {
+            assert_eq!(directory.pixmap(0, index).unwrap().as_ref(), Some(pixmap));
+        }
+        assert!(directory.pixmap(0, 4).unwrap().is_none());
+        assert!(directory.pixmap_table(2).unwrap().is_none());
+        assert_eq!(
+            directory.font_table(0).unwrap().unwrap().fonts[0].name,
+            "SampleToyFont"
+        );
+
+        // Records are only decoded on demand, so a bad font type is not found by the scan.
+        let mut layout = layout;
+        layout.compact = false;
+        let mut buffer = layout_to_data(&layout).unwrap();
+        let author = buffer
+            .windows(13)
+            .position(|window| window == b"The-Nice-One\0")
+            .unwrap();
+        buffer[author + 14] = 0xff;
+        let directory = TableDirectory::scan(&buffer).unwrap();
+        assert_eq!(directory.entries().len(), 5);
+        assert!(directory.font_table(0).is_err());
+        assert_eq!(
+            directory.character_table(0).unwrap().unwrap(),
+            layout.character_tables[0]
+        );
+    }
+
+    #[test]
+    fn compare_layouts_semantically() {
+        init_logger();
+
+        let layout = sample_layout();
+        let round_tripped = layout_from_data(&layout_to_data(&layout).unwrap()).unwrap();
+        assert_eq!(round_tripped, layout);
+
+        let mut padded = layout.clone();
+        padded.compact = false;
+        let pixmap_table = &mut padded.pixmap_tables[0];
+        pixmap_table.configuration_flags = PixmapTableConfigurationFlags::ConstantBitsPerPixel;
+        for pixmap in &mut pixmap_table.pixmaps {
+            pixmap.custom_height = pixmap_table.constant_height;
+        }
+        pixmap_table.constant_height = None;
+        pixmap_table.pixmaps[1].data[2] |= 0b11110000;
+        padded.character_tables[0].characters[0].advance_x = Some(9);
+
+        assert_ne!(padded, layout);
+        assert!(padded.semantically_eq(&layout));
+        assert!(layout_from_data(&layout_to_data(&padded).unwrap())
+            .unwrap()
+            .semantically_eq(&layout));
+
+        padded.pixmap_tables[0].pixmaps[1].data[0] ^= 0b00000001;
+        assert!(!padded.semantically_eq(&layout));
+
+        let mut too_wide = layout.clone();
+        too_wide.pixmap_tables[0].constant_bits_per_pixel = Some(9);
+        assert!(too_wide.semantically_eq(&too_wide.clone()));
+        assert!(!too_wide.semantically_eq(&layout));
+        let mut changed = too_wide.clone();
+        changed.pixmap_tables[0].pixmaps[0].data[0] ^= 0b00000001;
+        assert!(!changed.semantically_eq(&too_wide));
+    }
+
+    #[test]
+    fn write_verified_layout() {
+        init_logger();
+
+        let mut layout = sample_layout();
+        assert_eq!(
+            layout_to_data_verified(&layout).unwrap(),
+            layout_to_data(&layout).unwrap()
+        );
+
+        layout.character_tables[0].characters[1].advance_x = Some(3);
+        match layout_to_data_verified(&layout) {
+            Err(SerializeError::RoundTripMismatch { field }) => {
+                assert_eq!(field, "character_tables[0].characters[1].advance_x")
+            }
+            result => panic!("expected a round trip mismatch, got {:?}", result),
+        }
+
+        layout.character_tables[0].modifier_flags = CharacterTableModifierFlags::UseAdvanceX;
+        for character in &mut layout.character_tables[0].characters {
+            character.advance_x.get_or_insert(4);
+        }
+        layout_to_data_verified(&layout).unwrap();
+    }
+
+    #[test]
+    fn write_and_read_bits() {
+        use spf::core::byte::{ByteReader, ByteReaderImpl, ByteWriter};
+
+        let mut writer = ByteWriter::new();
+        writer.write_bits(0b101, 3);
+        writer.write_bits(0xBEEF, 16);
+        assert_eq!(writer.bit_position(), 19);
+        writer.align_to_byte();
+        assert_eq!(writer.bit_position(), 24);
+        writer.write_bits(0xFFFF_FF2A, 8);
+        writer.write_bits(0xDEADBEEF, 32);
+        let bytes = writer.into_bytes();
+        assert_eq!(
+            bytes,
+            vec![0b01111101, 0b11110111, 0b00000101, 0x2A, 0xEF, 0xBE, 0xAD, 0xDE]
+        );
+
+        let mut reader = ByteReaderImpl::new(&bytes);
+        assert_eq!(reader.read_bits(3).unwrap(), 0b101);
+        assert_eq!(reader.read_bits(16).unwrap(), 0xBEEF);
+        assert_eq!(reader.bit_position(), 19);
+        assert_eq!(reader.align_to_byte().unwrap(), 0);
+        assert_eq!(reader.align_to_byte().unwrap(), 0);
+        assert_eq!(reader.read_bits(8).unwrap(), 0x2A);
+        assert_eq!(reader.read_bits(32).unwrap(), 0xDEADBEEF);
+        assert!(reader.read_bits(1).is_err());
+    }
+
+    #[test]
+    fn build_layout_with_builder() {
+        init_logger();
+
+        let mut expected = sample_layout();
+        expected.pixmap_tables.truncate(1);
+
+        let mut builder = LayoutBuilder::new();
+        builder.compact(true);
+        let palette = builder
+            .add_color_table()
+            .color(0, 0, 0, 0)
+            .color(36, 174, 214, 255)
+            .handle();
+        let pixmaps = builder
+            .add_pixmap_table(&[palette])
+            .constant_height(4)
+            .constant_bits_per_pixel(1)
+            .handle();
+
+        let mut glyphs = Vec::new();
+        for pixmap in &expected.pixmap_tables[0].pixmaps {
+            let width = pixmap.custom_width.unwrap();
+            let pixels: Vec<u8> = (0..width as usize * 4)
+                .map(|index| (pixmap.data[index / 8] >> (index % 8)) & 1)
+                .collect();
+            glyphs.push(builder.add_pixmap(pixmaps, width, 4, 1, &pixels));
+        }
+
+        let characters = builder
+            .add_character_table()
+            .add_character('o', glyphs[0])
+            .add_character('w', glyphs[1])
+            .add_character('😊', glyphs[2])
+            .add_character("!=", glyphs[3])
+            .handle();
+        builder
+            .add_font("SampleToyFont", "The-Nice-One")
+            .character_table(characters);
+
+        let layout = builder.build().unwrap();
+        assert!(layout.semantically_eq(&expected));
+        assert_eq!(layout, expected);
+
+        let mut builder = LayoutBuilder::new();
+        let palette = builder.add_color_table().color(0, 0, 0, 255).handle();
+        let pixmaps = builder.add_pixmap_table(&[palette]).handle();
+        let glyph = builder.add_pixmap(pixmaps, 2, 1, 2, &[0, 3]);
+        builder.add_character_table().add_character('.', glyph);
+        let errors = builder.build().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].table_type, Some(TableType::Pixmap));
+
+        for pixels in [&[1, 0, 1][..], &[1, 3]] {
+            let mut builder = LayoutBuilder::new();
+            let palette = builder
+                .add_color_table()
+                .color(0, 0, 0, 255)
+                .color(255, 255, 255, 255)
+                .handle();
+            let pixmaps = builder.add_pixmap_table(&[palette]).handle();
+            let glyph = builder.add_pixmap(pixmaps, 2, 1, 1, pixels);
+            builder.add_character_table().add_character('.', glyph);
+            let errors = builder.build().unwrap_err();
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors[0].table_type, Some(TableType::Pixmap));
+            assert_eq!(errors[0].record_index, Some(0));
+        }
+    }
+
+    #[test]
+    fn read_and_write_pixels() {
+        let mut pixmap_table = sample_pixmap_table();
+
+        let rows: Vec<Vec<u8>> = pixmap_table
+            .rows(0)
+            .unwrap()
+            .map(|row| row.collect())
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                vec![1, 1, 1, 1],
+                vec![1, 0, 0, 1],
+                vec![1, 0, 0, 1],
+                vec![1, 1, 1, 1]
+            ]
+        );
+        assert_eq!(pixmap_table.pixels(1).unwrap().len(), 20);
+        assert_eq!(pixmap_table.pixel(0, 1, 1), Some(0));
+        assert_eq!(pixmap_table.pixel(0, 4, 0), None);
+        assert_eq!(pixmap_table.pixel(4, 0, 0), None);
+
+        assert!(pixmap_table.set_pixel(0, 1, 1, 1));
+        assert_eq!(pixmap_table.pixel(0, 1, 1), Some(1));
+        assert_eq!(pixmap_table.pixmaps[0].data, vec![0b10111111, 0b11111001]);
+        assert!(!pixmap_table.set_pixel(0, 1, 1, 2));
+        assert!(!pixmap_table.set_pixel(0, 0, 4, 1));
+
+        let mut too_wide = pixmap_table.clone();
+        too_wide.constant_bits_per_pixel = None;
+        too_wide.pixmaps[0].custom_bits_per_pixel = Some(9);
+        assert_eq!(too_wide.pixel(0, 0, 0), None);
+        assert!(too_wide.pixels(0).is_none());
+        assert!(too_wide.rows(0).is_none());
+        assert!(!too_wide.set_pixel(0, 0, 0, 1));
+        assert_eq!(too_wide.pixmaps[0].data, pixmap_table.pixmaps[0].data);
+
+        let indices: Vec<u8> = rows.concat();
+        let pixmap = Pixmap::from_indices(4, 4, 1, &indices).unwrap();
+        assert_eq!(pixmap.data, sample_pixmap_table().pixmaps[0].data);
+        let pixmap = Pixmap::from_indices(2, 1, 7, &[66, 15]).unwrap();
+        assert_eq!(pixmap.data, vec![0b11000010, 0b00000111]);
+        assert!(Pixmap::from_indices(4, 4, 1, &indices[1..]).is_err());
+        assert!(Pixmap::from_indices(1, 1, 1, &[2]).is_err());
+    }
+
+    #[test]
+    fn look_up_characters() {
+        let mut layout = sample_layout();
+        let index = CharacterIndex::for_font(&layout, 0, 0).unwrap();
+        assert_eq!(index.len(), 4);
+
+        let resolved = index.get('w').unwrap();
+        assert_eq!(resolved.character.code_points, "w");
+        assert_eq!(resolved.pixmap_table_index, 0);
+        assert_eq!(resolved.pixmap_index, 1);
+        assert_eq!(resolved.pixmap, &layout.pixmap_tables[0].pixmaps[1]);
+        assert_eq!(resolved.advance_x, 5);
+
+        assert_eq!(index.get_cluster("!=").unwrap().pixmap_index, 3);
+        assert_eq!(index.get_cluster("😊"), index.get('😊'));
+        assert!(index.get('!').is_none());
+        assert!(index.get('x').is_none());
+        assert!(CharacterIndex::for_font(&layout, 0, 1).is_none());
+
+        let table = &mut layout.character_tables[0];
+        table.modifier_flags = CharacterTableModifierFlags::UseAdvanceX;
+        for character in &mut table.characters {
+            character.advance_x = Some(6);
+        }
+        let index = CharacterIndex::from_tables(&layout, [0]);
+        assert_eq!(index.get('o').unwrap().advance_x, 6);
+    }
+
+    #[test]
+    fn segment_text_into_glyphs() {
+        let layout = sample_layout();
+        let index = CharacterIndex::for_font(&layout, 0, 0).unwrap();
+
+        let glyphs = index.segment("o!=😊!", Some('w'));
+        let texts: Vec<_> = glyphs.iter().map(|glyph| glyph.text).collect();
+        assert_eq!(texts, vec!["o", "!=", "😊", "!"]);
+        let offsets: Vec<_> = glyphs.iter().map(|glyph| glyph.offset).collect();
+        assert_eq!(offsets, vec![0, 1, 3, 7]);
+        assert_eq!(glyphs[1].glyph.unwrap().pixmap_index, 3);
+        assert!(!glyphs[1].is_replacement);
+        assert!(glyphs[3].is_replacement);
+        assert_eq!(glyphs[3].glyph, index.get('w'));
+
+        let glyphs = index.segment("=!x", None);
+        assert_eq!(glyphs.len(), 3);
+        assert!(glyphs
+            .iter()
+            .all(|glyph| glyph.is_replacement && glyph.glyph.is_none()));
+        assert!(index.segment("", None).is_empty());
+
+        let mut layout = layout;
+        layout.character_tables[0].characters[1].code_points = "\u{FFFD}".to_string();
+        let font = layout.resolve_font("SampleToyFont").unwrap();
+        let glyphs = font.segment("!=o?");
+        let texts: Vec<_> = glyphs.iter().map(|glyph| glyph.text).collect();
+        assert_eq!(texts, vec!["!=", "o", "?"]);
+        assert!(glyphs[2].is_replacement);
+        assert_eq!(glyphs[2].glyph, font.characters().get('\u{FFFD}'));
+        assert!(glyphs[2].glyph.is_some());
+    }
+
+    #[test]
+    fn resolve_font_links() {
+        let mut layout = sample_layout();
+        let font = layout.resolve_font("SampleToyFont").unwrap();
+        assert_eq!(font.font().author, "The-Nice-One");
+        assert_eq!(font.characters().len(), 4);
+
+        let glyph = font.glyph('o').unwrap();
+        assert_eq!(glyph.palette.len(), 2);
+        assert_eq!(glyph.color(0, 0).unwrap().alpha, 255);
+        assert_eq!(glyph.color(1, 1).unwrap().alpha, 0);
+        assert!(glyph.color(4, 0).is_none());
+        assert!(std::ptr::eq(
+            glyph.palette,
+            font.glyph('w').unwrap().palette
+        ));
+
+        let mut constant_alpha = layout.clone();
+        let color_table = &mut constant_alpha.color_tables[0];
+        color_table.configuration_flags |= ColorTableConfigurationFlags::ConstantAlpha;
+        color_table.constant_alpha = Some(128);
+        color_table.modifier_flags |= ColorTableModifierFlags::UseColorType;
+        for color in &mut color_table.colors {
+            color.custom_alpha = None;
+            color.color_type = Some(ColorType::Absolute);
+        }
+        let font = constant_alpha.resolve_font("SampleToyFont").unwrap();
+        let color = font.glyph('o').unwrap().color(0, 0).unwrap();
+        assert_eq!(color.alpha, 128);
+        assert_eq!(color.color_type, ColorType::Absolute);
+        assert_eq!(font.cluster("!=").unwrap().character.pixmap_index, 3);
+        assert!(font.glyph('x').is_none());
+
+        assert_eq!(
+            layout.resolve_font("MissingFont").unwrap_err(),
+            ResolveError::FontNotFound
+        );
+
+        layout.character_tables[0].pixmap_table_indexes = Some(vec![5]);
+        assert_eq!(
+            layout.resolve_font("SampleToyFont").unwrap_err(),
+            ResolveError::DanglingLink {
+                table_type: TableType::Character,
+                table_index: 0,
+                record_index: None,
+                target: TableType::Pixmap,
+                index: 5,
+            }
+        );
+    }
+
+    #[test]
+    fn optimize_layout_size() {
+        let mut layout = sample_layout();
+        layout.compact = false;
+        let pixmap_table = &mut layout.pixmap_tables[0];
+        pixmap_table.constant_height = None;
+        for pixmap in &mut pixmap_table.pixmaps {
+            pixmap.custom_height = Some(4);
+        }
+        for (character, width) in layout.character_tables[0]
+            .characters
+            .iter_mut()
+            .zip([4, 5, 4, 4])
+        {
+            character.advance_x = Some(width);
+        }
+        layout.color_tables[0].constant_alpha = None;
+        layout.normalize();
+        let wasteful = layout.clone();
+
+        layout.optimize();
+        assert!(layout.semantically_eq(&wasteful));
+        assert!(layout.compact);
+        assert_eq!(layout.pixmap_tables[0].constant_height, Some(4));
+        assert_eq!(layout.pixmap_tables[0].pixmaps[0].custom_height, None);
+        assert!(layout.character_tables[0]
+            .characters
+            .iter()
+            .all(|character| character.advance_x.is_none()));
+
+        let optimized = layout_to_data(&layout).unwrap();
+        assert!(optimized.len() < layout_to_data(&wasteful).unwrap().len());
+        assert!(optimized.len() <= layout_to_data(&sample_layout()).unwrap().len());
+        assert_eq!(layout_from_data(&optimized).unwrap(), layout);
+
+        let mut again = layout.clone();
+        again.optimize();
+        assert_eq!(again, layout);
+    }
+
+    #[test]
+    fn shard_oversized_tables() {
+        let mut layout = sample_layout();
+        let color_table = &mut layout.color_tables[0];
+        for red in 0..298 {
+            let mut color = Color::default();
+            color.custom_alpha = Some(255);
+            color.red = red as u8;
+            color_table.colors.push(color);
+        }
+
+        let mut pixmap_table = PixmapTable::default();
+        pixmap_table.constant_width = Some(1);
+        pixmap_table.constant_height = Some(1);
+        pixmap_table.constant_bits_per_pixel = Some(1);
+        pixmap_table.color_table_indexes = Some(vec![0]);
+        let mut character_table = CharacterTable::default();
+        character_table.pixmap_table_indexes = Some(vec![1]);
+        for index in 0..600 {
+            let mut pixmap = Pixmap::default();
+            pixmap.data = vec![(index % 2) as u8];
+            pixmap_table.pixmaps.push(pixmap);
+
+            let mut character = Character::default();
+            character.code_points = char::from_u32(0x4E00 + index).unwrap().to_string();
+            character_table.characters.push(character);
+        }
+        layout.pixmap_tables[1] = pixmap_table;
+        layout.character_tables.push(character_table);
+        layout.font_tables[0].character_table_indexes = Some(vec![0, 1]);
+        layout.font_tables[0].fonts[0].linked_character_table_indexes = vec![1, 0];
+        layout.normalize();
+
+        assert!(matches!(
+            layout_to_data(&layout),
+            Err(SerializeError::StaticVectorTooLarge)
+        ));
+
+        let mut sharded = layout.clone();
+        sharded.shard().unwrap();
+        assert_eq!(sharded.color_tables.len(), 2);
+        assert_eq!(sharded.pixmap_tables.len(), 4);
+        assert_eq!(sharded.character_tables.len(), 4);
+        assert_eq!(
+            sharded.pixmap_tables[0].color_table_indexes,
+            Some(vec![0, 1])
+        );
+        assert_eq!(
+            sharded.character_tables[1].pixmap_table_indexes,
+            Some(vec![1, 2, 3])
+        );
+        assert_eq!(
+            sharded.font_tables[0].character_table_indexes,
+            Some(vec![0, 1, 2, 3])
+        );
+        assert_eq!(
+            sharded.font_tables[0].fonts[0].linked_character_table_indexes,
+            vec![1, 2, 3, 0]
+        );
+
+        let index = CharacterIndex::for_font(&sharded, 0, 0).unwrap();
+        assert_eq!(index.len(), 604);
+        for code_point in [0, 254, 255, 599] {
+            let resolved = index.get(char::from_u32(0x4E00 + code_point).unwrap());
+            let pixmap = &layout.pixmap_tables[1].pixmaps[code_point as usize];
+            assert_eq!(resolved.unwrap().pixmap, pixmap);
+        }
+        assert_eq!(index.get('o').unwrap().pixmap_index, 0);
+
+        let mut options = SerializeOptions::default();
+        options.shard = true;
+        let data = layout_to_data_with_options(&layout, options).unwrap();
+        assert!(layout_from_data(&data).unwrap().semantically_eq(&sharded));
+    }
 }